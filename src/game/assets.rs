@@ -1,8 +1,14 @@
+use std::marker::PhantomData;
+
 use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
     prelude::*,
     render::texture::{ImageLoaderSettings, ImageSampler},
     utils::HashMap,
 };
+use serde::Deserialize;
+
+use super::{spawn::building::BuildingCatalogue, wave::WaveScript};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<HandleMap<ImageKey>>();
@@ -16,6 +22,9 @@ pub(super) fn plugin(app: &mut App) {
 
     app.register_type::<HandleMap<ObjectKey>>();
     app.init_resource::<HandleMap<ObjectKey>>();
+
+    add_data_asset::<BuildingCatalogue>(app);
+    add_data_asset::<WaveScript>(app);
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
@@ -135,7 +144,7 @@ impl FromWorld for HandleMap<SoundtrackKey> {
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect, Deserialize)]
 pub enum ObjectKey {
     ShipBody,
     ShipTurret,
@@ -215,3 +224,77 @@ impl<K: AssetKey> HandleMap<K> {
             .all(|x| asset_server.is_loaded_with_dependencies(x))
     }
 }
+
+/// Game data authored in a RON file. Once loaded, the asset is copied into a
+/// resource of the same type, so systems can simply use `Res<T>`.
+pub trait DataAsset: Asset + Resource + Clone + for<'de> Deserialize<'de> {
+    /// File extensions that identify this kind of data, e.g. `buildings.ron`.
+    const EXTENSIONS: &'static [&'static str];
+    /// Path of the file that the game loads.
+    const PATH: &'static str;
+}
+
+fn add_data_asset<T: DataAsset>(app: &mut App) {
+    app.init_asset::<T>();
+    app.register_asset_loader(RonLoader::<T>::default());
+    app.init_resource::<DataHandle<T>>();
+    app.add_systems(PreUpdate, sync_data_asset::<T>);
+}
+
+/// Keeps the data asset loaded.
+#[derive(Resource)]
+struct DataHandle<T: DataAsset> {
+    _handle: Handle<T>,
+}
+
+impl<T: DataAsset> FromWorld for DataHandle<T> {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            _handle: world.resource::<AssetServer>().load(T::PATH),
+        }
+    }
+}
+
+/// Copies the data asset into its resource whenever it is (re)loaded.
+fn sync_data_asset<T: DataAsset>(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<T>>,
+    assets: Res<Assets<T>>,
+) {
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            if let Some(data) = assets.get(*id) {
+                commands.insert_resource(data.clone());
+            }
+        }
+    }
+}
+
+struct RonLoader<T>(PhantomData<T>);
+
+impl<T> Default for RonLoader<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: DataAsset> AssetLoader for RonLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<T, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        T::EXTENSIONS
+    }
+}
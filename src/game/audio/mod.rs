@@ -1,3 +1,4 @@
+pub mod settings;
 pub mod sfx;
 pub mod soundtrack;
 
@@ -6,3 +7,8 @@ use bevy::prelude::*;
 pub fn plugin(app: &mut App) {
     app.add_plugins((sfx::plugin, soundtrack::plugin));
 }
+
+/// The audio settings, without the plugins that need audio output.
+pub fn headless_plugin(app: &mut App) {
+    app.add_plugins(settings::plugin);
+}
@@ -0,0 +1,60 @@
+//! Volume settings chosen by the player.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::storage::Storage;
+
+/// Key of the audio settings in [`Storage`].
+pub const AUDIO_SETTINGS_KEY: &str = "audio";
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<AudioSettings>();
+    app.add_systems(Startup, load_audio_settings);
+}
+
+/// Volume levels between 0 and 1. These scale the [`GlobalVolume`], which
+/// sets the overall mix. Persisted in [`Storage`] under
+/// [`AUDIO_SETTINGS_KEY`].
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub muted: bool,
+}
+impl AudioSettings {
+    pub fn music_volume(&self) -> f32 {
+        self.volume(self.music)
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.volume(self.sfx)
+    }
+
+    fn volume(&self, channel: f32) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master * channel
+        }
+    }
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 1.0,
+            sfx: 1.0,
+            muted: false,
+        }
+    }
+}
+
+fn load_audio_settings(mut commands: Commands, storage: Res<Storage>) {
+    if let Some(settings) = storage.load::<AudioSettings>(AUDIO_SETTINGS_KEY) {
+        commands.insert_resource(settings);
+    }
+}
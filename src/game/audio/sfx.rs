@@ -1,5 +1,9 @@
-use bevy::{audio::PlaybackMode, prelude::*};
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
 
+use super::settings::AudioSettings;
 use crate::game::assets::{HandleMap, SfxKey};
 
 pub(super) fn plugin(app: &mut App) {
@@ -10,6 +14,7 @@ fn play_sfx(
     trigger: Trigger<PlaySfx>,
     mut commands: Commands,
     sfx_handles: Res<HandleMap<SfxKey>>,
+    settings: Res<AudioSettings>,
 ) {
     let sfx_key = match trigger.event() {
         PlaySfx::Key(key) => *key,
@@ -18,6 +23,7 @@ fn play_sfx(
         source: sfx_handles[&sfx_key].clone_weak(),
         settings: PlaybackSettings {
             mode: PlaybackMode::Despawn,
+            volume: Volume::new(settings.sfx_volume()),
             ..default()
         },
     });
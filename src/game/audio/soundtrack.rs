@@ -1,10 +1,18 @@
-use bevy::{audio::PlaybackMode, prelude::*};
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
 
+use super::settings::AudioSettings;
 use crate::game::assets::{HandleMap, SoundtrackKey};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<IsSoundtrack>();
     app.observe(play_soundtrack);
+    app.add_systems(
+        Update,
+        update_soundtrack_volume.run_if(resource_changed::<AudioSettings>),
+    );
 }
 
 fn play_soundtrack(
@@ -12,6 +20,7 @@ fn play_soundtrack(
     mut commands: Commands,
     soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
     soundtrack_query: Query<Entity, With<IsSoundtrack>>,
+    settings: Res<AudioSettings>,
 ) {
     for entity in &soundtrack_query {
         commands.entity(entity).despawn_recursive();
@@ -26,6 +35,7 @@ fn play_soundtrack(
             source: soundtrack_handles[&soundtrack_key].clone_weak(),
             settings: PlaybackSettings {
                 mode: PlaybackMode::Loop,
+                volume: Volume::new(settings.music_volume()),
                 ..default()
             },
         },
@@ -33,6 +43,16 @@ fn play_soundtrack(
     ));
 }
 
+/// Apply volume changes to the soundtrack that is already playing.
+fn update_soundtrack_volume(
+    settings: Res<AudioSettings>,
+    sink_query: Query<&AudioSink, With<IsSoundtrack>>,
+) {
+    for sink in &sink_query {
+        sink.set_volume(settings.music_volume());
+    }
+}
+
 /// Trigger this event to play or disable the soundtrack.
 /// Playing a new soundtrack will overwrite the previous one.
 /// Soundtracks will loop.
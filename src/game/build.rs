@@ -9,12 +9,16 @@ use super::{
     audio::sfx::PlaySfx,
     camera::CameraTarget,
     gameplay::Resources,
+    input::{Action, Actions},
     notifications::Notification,
-    phase::GamePhase,
+    phase::{GamePhase, SkipBuildPhase},
     spawn::{
-        asteroid::{Asteroid, ASTEROID_WAYPOINT_COLOR},
-        building::{BuildingType, Destructable, SpawnBuilding},
+        asteroid::{nearest_asteroids_report, Asteroid},
+        building::{Building, BuildingCatalogue, BuildingType, SpawnBuilding, DEFAULT_FOOTPRINT},
+        station::STATION_RADIUS,
     },
+    stats::RunStats,
+    turret::Turret,
     waypoint::Waypointed,
 };
 
@@ -30,6 +34,8 @@ pub(super) fn plugin(app: &mut App) {
             update_mouse_pos,
             exit_build_mode_on_esc,
             toggle_camera_distance,
+            toggle_grid_snap,
+            ready_up,
         )
             .run_if(in_state(GamePhase::Build))
             .in_set(AppSet::RecordInput),
@@ -37,8 +43,16 @@ pub(super) fn plugin(app: &mut App) {
     app.add_systems(
         Update,
         (
-            (handle_build_action, listen_for_build_mode, update_marker).chain(),
+            (
+                handle_build_action,
+                listen_for_build_mode,
+                update_marker,
+                update_placement_problem_text,
+            )
+                .chain(),
+            handle_ready_action,
             scan,
+            cycle_turret_targeting,
         )
             .run_if(in_state(GamePhase::Build))
             .in_set(AppSet::Update),
@@ -50,12 +64,92 @@ pub(super) fn plugin(app: &mut App) {
 pub struct BuildLocationMarker {
     mouse_world_pos: Vec2,
     pub mode: Option<BuildingType>,
+    /// Round placement positions to the nearest point on a [`GRID_SIZE`] grid.
+    pub snap_to_grid: bool,
+    /// Why the building being placed can't go where it is, if it can't.
+    pub problem: Option<PlacementProblem>,
     just_clicked: bool,
     click_debounce: Timer,
     white_material: Handle<StandardMaterial>,
     red_material: Handle<StandardMaterial>,
 }
 
+impl BuildLocationMarker {
+    /// The point on the build plane under the cursor.
+    pub fn mouse_world_pos(&self) -> Vec2 {
+        self.mouse_world_pos
+    }
+
+    /// Where a building would be placed.
+    pub fn placement_position(&self) -> Vec2 {
+        if self.snap_to_grid {
+            (self.mouse_world_pos / GRID_SIZE).round() * GRID_SIZE
+        } else {
+            self.mouse_world_pos
+        }
+    }
+}
+
+/// Spacing of the placement grid.
+pub const GRID_SIZE: f32 = 5.0;
+
+/// How far from the centre of the station buildings can be placed.
+pub const MAX_BUILD_RADIUS: f32 = 80.0;
+
+/// Why a building can't be placed somewhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum PlacementProblem {
+    TooFarFromStation,
+    InsideStation,
+    OnAsteroid,
+    TooCloseToBuilding,
+}
+impl PlacementProblem {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlacementProblem::TooFarFromStation => "Too far from the station",
+            PlacementProblem::InsideStation => "Inside the station's ring",
+            PlacementProblem::OnAsteroid => "Blocked by an asteroid",
+            PlacementProblem::TooCloseToBuilding => "Too close to existing structure",
+        }
+    }
+}
+
+/// Check whether a building with the given footprint fits at `position`.
+/// `buildings` are the positions and footprints of the buildings already
+/// placed, and `asteroids` the positions and radii of the asteroids.
+fn placement_problem(
+    position: Vec2,
+    footprint: f32,
+    buildings: impl IntoIterator<Item = (Vec2, f32)>,
+    asteroids: impl IntoIterator<Item = (Vec2, f32)>,
+) -> Option<PlacementProblem> {
+    let distance_from_station = position.length();
+    if distance_from_station > MAX_BUILD_RADIUS {
+        return Some(PlacementProblem::TooFarFromStation);
+    }
+    if distance_from_station < STATION_RADIUS + footprint {
+        return Some(PlacementProblem::InsideStation);
+    }
+    if asteroids
+        .into_iter()
+        .any(|(asteroid, radius)| asteroid.distance(position) < radius + footprint)
+    {
+        return Some(PlacementProblem::OnAsteroid);
+    }
+    if buildings
+        .into_iter()
+        .any(|(building, other)| building.distance(position) < footprint + other)
+    {
+        return Some(PlacementProblem::TooCloseToBuilding);
+    }
+    None
+}
+
+/// Text next to the cursor explaining why a building can't be placed.
+#[derive(Component)]
+struct PlacementProblemText;
+
 #[derive(Event, Debug)]
 pub struct EnterBuildMode(pub BuildingType);
 
@@ -65,7 +159,7 @@ fn listen_for_build_mode(
 ) {
     for event in event_reader.read() {
         for mut marker in &mut marker_query {
-            marker.mode = Some(event.0);
+            marker.mode = Some(event.0.clone());
             marker
                 .click_debounce
                 .set_duration(Duration::from_secs_f32(0.5));
@@ -89,6 +183,7 @@ fn init_marker(
     let red_material = materials.add(Color::srgb(1.0, 0.0, 0.0));
     commands.spawn((
         BuildLocationMarker {
+            snap_to_grid: true,
             white_material: white_material.clone(),
             red_material,
             ..Default::default()
@@ -101,23 +196,53 @@ fn init_marker(
         },
         StateScoped(Screen::Playing),
     ));
+    commands.spawn((
+        Name::new("PlacementProblem"),
+        PlacementProblemText,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::srgb(1.0, 0.3, 0.3),
+                ..Default::default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            ..Default::default()
+        }),
+        StateScoped(Screen::Playing),
+    ));
 }
 
 fn reset_marker(
     mut marker_query: Query<(&mut BuildLocationMarker, &mut Visibility)>,
+    mut text_query: Query<
+        &mut Visibility,
+        (With<PlacementProblemText>, Without<BuildLocationMarker>),
+    >,
     mut resources: ResMut<Resources>,
+    catalogue: Res<BuildingCatalogue>,
 ) {
     for (mut marker, mut visibility) in marker_query.iter_mut() {
-        if let Some(building_type) = marker.mode.take() {
+        if let Some(definition) = marker
+            .mode
+            .take()
+            .and_then(|building_type| catalogue.get(&building_type))
+        {
             // refund resources
-            resources.delivered += building_type.cost();
+            resources.delivered += definition.cost;
         }
+        marker.problem = None;
+        *visibility = Visibility::Hidden;
+    }
+    for mut visibility in &mut text_query {
         *visibility = Visibility::Hidden;
     }
 }
 
 fn update_mouse_pos(
-    mouse_input: Res<ButtonInput<MouseButton>>,
+    actions: Actions,
     camera: Query<(&Camera, &GlobalTransform)>,
     window: Query<&Window, With<PrimaryWindow>>,
     mut marker_query: Query<&mut BuildLocationMarker>,
@@ -141,20 +266,25 @@ fn update_mouse_pos(
     // Record mouse click.
     for mut marker in &mut marker_query {
         marker.just_clicked =
-            mouse_input.just_released(MouseButton::Left) && marker.click_debounce.finished();
+            actions.just_released(Action::Select) && marker.click_debounce.finished();
     }
 }
 
 fn exit_build_mode_on_esc(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
     mut marker_query: Query<&mut BuildLocationMarker>,
     mut resources: ResMut<Resources>,
+    catalogue: Res<BuildingCatalogue>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(Action::Cancel) {
         for mut marker in &mut marker_query {
-            if let Some(building_type) = marker.mode.take() {
+            if let Some(definition) = marker
+                .mode
+                .take()
+                .and_then(|building_type| catalogue.get(&building_type))
+            {
                 // refund resources
-                resources.delivered += building_type.cost();
+                resources.delivered += definition.cost;
             }
         }
     }
@@ -171,40 +301,54 @@ fn update_marker(
         Changed<BuildLocationMarker>,
     >,
     mut commands: Commands,
-    buildings_query: Query<&Transform, (With<Destructable>, Without<BuildLocationMarker>)>,
+    buildings_query: Query<(&Transform, &Building), Without<BuildLocationMarker>>,
+    asteroids_query: Query<(&Asteroid, &Transform), Without<BuildLocationMarker>>,
+    catalogue: Res<BuildingCatalogue>,
     mut notification_writer: EventWriter<Notification>,
+    mut stats: ResMut<RunStats>,
 ) {
+    let footprint = |building_type: &BuildingType| {
+        catalogue
+            .get(building_type)
+            .map_or(DEFAULT_FOOTPRINT, |definition| definition.footprint)
+    };
     for (mut marker, mut transform, mut visibility, mut material) in marker_query.iter_mut() {
-        transform.translation = marker.mouse_world_pos.extend(0.0);
-        let mut can_build = true;
-        for building_transform in buildings_query.iter() {
-            if building_transform
-                .translation
-                .xy()
-                .distance_squared(marker.mouse_world_pos)
-                < 100.0
-            {
-                can_build = false;
-                break;
-            }
-        }
-        if can_build {
+        let position = marker.placement_position();
+        transform.translation = position.extend(0.0);
+        let problem = marker.mode.as_ref().and_then(|building_type| {
+            placement_problem(
+                position,
+                footprint(building_type),
+                buildings_query.iter().map(|(transform, building)| {
+                    (
+                        transform.translation.xy(),
+                        footprint(&building.building_type),
+                    )
+                }),
+                asteroids_query
+                    .iter()
+                    .map(|(asteroid, transform)| (transform.translation.xy(), asteroid.radius)),
+            )
+        });
+        marker.problem = problem;
+        if problem.is_none() {
             *material = marker.white_material.clone();
         } else {
             *material = marker.red_material.clone();
         }
         if marker.just_clicked && marker.mode.is_some() {
-            if can_build {
-                if let Some(building_type) = marker.mode.take() {
-                    commands.trigger(SpawnBuilding {
-                        building_type,
-                        position: marker.mouse_world_pos.extend(0.0),
-                    });
-                    commands.trigger(PlaySfx::Key(SfxKey::Build));
-                }
-            } else {
-                notification_writer
-                    .send(Notification("Too close to existing structure".to_string()));
+            if let Some(problem) = problem {
+                notification_writer.send(Notification(problem.label().to_string()));
+            } else if let Some(building_type) = marker.mode.take() {
+                commands.trigger(SpawnBuilding {
+                    building_type,
+                    position: position.extend(0.0),
+                    health: None,
+                    level: 0,
+                    targeting: None,
+                });
+                stats.current().buildings_placed += 1;
+                commands.trigger(PlaySfx::Key(SfxKey::Build));
             }
         }
         *visibility = if marker.mode.is_some() {
@@ -215,58 +359,130 @@ fn update_marker(
     }
 }
 
+/// Show the reason a building can't be placed next to the marker.
+fn update_placement_problem_text(
+    marker_query: Query<&BuildLocationMarker, Changed<BuildLocationMarker>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut text_query: Query<(&mut Text, &mut Style, &mut Visibility), With<PlacementProblemText>>,
+) {
+    let Ok(marker) = marker_query.get_single() else {
+        return;
+    };
+    let Ok((mut text, mut style, mut visibility)) = text_query.get_single_mut() else {
+        return;
+    };
+    let screen_position = camera_query
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| {
+            camera.world_to_viewport(transform, marker.placement_position().extend(0.0))
+        });
+    match (marker.problem, screen_position) {
+        (Some(problem), Some(screen_position)) => {
+            text.sections[0].value = problem.label().to_string();
+            style.left = Val::Px(screen_position.x + 20.0);
+            style.top = Val::Px(screen_position.y + 20.0);
+            *visibility = Visibility::Visible;
+        }
+        _ => *visibility = Visibility::Hidden,
+    }
+}
+
+fn toggle_grid_snap(
+    actions: Actions,
+    mut marker_query: Query<&mut BuildLocationMarker>,
+    mut notification_writer: EventWriter<Notification>,
+) {
+    if !actions.just_pressed(Action::ToggleSnap) {
+        return;
+    }
+    for mut marker in &mut marker_query {
+        marker.snap_to_grid = !marker.snap_to_grid;
+        notification_writer.send(Notification(format!(
+            "Grid snapping {}",
+            if marker.snap_to_grid { "on" } else { "off" }
+        )));
+    }
+}
+
 #[derive(Component, Debug)]
 pub struct BuildAction {
     pub building_type: BuildingType,
 }
 
+/// The dock button that ends the Build phase early.
+#[derive(Component, Debug)]
+pub struct ReadyAction;
+
+fn handle_ready_action(mut commands: Commands, mut button_query: InteractionQuery<&ReadyAction>) {
+    for (interaction, _) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            commands.trigger(SkipBuildPhase);
+        }
+    }
+}
+
+fn ready_up(mut commands: Commands, actions: Actions) {
+    if actions.just_pressed(Action::Ready) {
+        commands.trigger(SkipBuildPhase);
+    }
+}
+
 fn handle_build_action(
     mut button_query: InteractionQuery<&BuildAction>,
     mut event_writer: EventWriter<EnterBuildMode>,
     mut notification_writer: EventWriter<Notification>,
     mut resources: ResMut<Resources>,
+    catalogue: Res<BuildingCatalogue>,
 ) {
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
-            if resources.delivered < action.building_type.cost() {
-                notification_writer.send(Notification("Not enough resources".to_string()));
+            let Some(definition) = catalogue.get(&action.building_type) else {
+                continue;
+            };
+            if !resources.delivered.spend(&definition.cost) {
+                notification_writer.send(Notification(format!(
+                    "Not enough resources - needs {}",
+                    definition.cost
+                )));
                 continue;
             }
-            resources.delivered -= action.building_type.cost();
-            event_writer.send(EnterBuildMode(action.building_type));
+            event_writer.send(EnterBuildMode(action.building_type.clone()));
         }
     }
 }
 
 fn scan(
-    mouse_input: Res<ButtonInput<MouseButton>>,
-    asteroids_query: Query<(Entity, &Transform), With<Asteroid>>,
+    actions: Actions,
+    asteroids_query: Query<(Entity, &Asteroid, &Transform)>,
     location_marker_query: Query<&BuildLocationMarker>,
     mut notification_writer: EventWriter<Notification>,
     mut commands: Commands,
 ) {
-    if mouse_input.just_pressed(MouseButton::Right) {
+    if actions.just_pressed(Action::Scan) {
         let marker = location_marker_query.iter().next().unwrap();
         let scan_pos = marker.mouse_world_pos;
-        let mut nearest_distance = f32::MAX;
-        for (entity, transform) in asteroids_query.iter() {
-            let distance = transform.translation.xy().distance(scan_pos);
-            if distance < nearest_distance {
-                nearest_distance = distance;
-            }
-            if distance < 25.0 {
+        let mut detected = Vec::new();
+        for (entity, asteroid, transform) in asteroids_query.iter() {
+            if transform.translation.xy().distance(scan_pos) < 25.0 {
                 commands
                     .entity(entity)
                     .insert(Visibility::Visible)
-                    .insert(Waypointed::new(ASTEROID_WAYPOINT_COLOR));
+                    .insert(Waypointed::new(asteroid.kind.color()));
+                detected.push(asteroid.kind.label());
             }
         }
-        if nearest_distance < 25.0 {
-            notification_writer.send(Notification("Asteroid detected".to_string()));
+        if detected.is_empty() {
+            notification_writer.send(Notification(nearest_asteroids_report(
+                scan_pos,
+                asteroids_query
+                    .iter()
+                    .map(|(_, asteroid, transform)| (asteroid, transform)),
+            )));
         } else {
             notification_writer.send(Notification(format!(
-                "Nearest asteroid: {:.2} units away",
-                nearest_distance,
+                "Asteroid detected: {}",
+                detected.join(", ")
             )));
         }
     }
@@ -275,9 +491,9 @@ fn scan(
 fn toggle_camera_distance(
     camera_target: Res<CameraTarget>,
     mut target_query: Query<&mut Transform>,
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
 ) {
-    if input.just_pressed(KeyCode::KeyZ) {
+    if actions.just_pressed(Action::ToggleZoom) {
         if let Some(target) = camera_target.0 {
             if let Ok(mut target_transform) = target_query.get_mut(target) {
                 if target_transform.translation.z < 250.0 {
@@ -289,3 +505,69 @@ fn toggle_camera_distance(
         }
     }
 }
+
+/// How close the cursor needs to be to a building to select it.
+pub const SELECT_RADIUS: f32 = 3.0;
+
+fn cycle_turret_targeting(
+    actions: Actions,
+    marker_query: Query<&BuildLocationMarker>,
+    mut turret_query: Query<(&mut Turret, &Transform, &Name)>,
+    mut notification_writer: EventWriter<Notification>,
+) {
+    if !actions.just_pressed(Action::CycleTargeting) {
+        return;
+    }
+    let Ok(marker) = marker_query.get_single() else {
+        return;
+    };
+    if marker.mode.is_some() {
+        return;
+    }
+    let closest = turret_query
+        .iter_mut()
+        .map(|(turret, transform, name)| {
+            let distance = transform.translation.xy().distance(marker.mouse_world_pos);
+            (turret, distance, name)
+        })
+        .filter(|(_, distance, _)| *distance < SELECT_RADIUS)
+        .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b));
+    if let Some((mut turret, _, name)) = closest {
+        turret.targeting = turret.targeting.next();
+        notification_writer.send(Notification(format!(
+            "{} targeting: {}",
+            name.as_str(),
+            turret.targeting.label()
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placement_checks_report_why_a_spot_is_invalid() {
+        let asteroid = (Vec2::new(50.0, 0.0), 7.0);
+        let building = (Vec2::new(0.0, 50.0), 5.0);
+        let check = |position| placement_problem(position, 5.0, [building], [asteroid]);
+
+        assert_eq!(check(Vec2::new(-50.0, 0.0)), None);
+        assert_eq!(
+            check(Vec2::new(0.0, -MAX_BUILD_RADIUS - 1.0)),
+            Some(PlacementProblem::TooFarFromStation)
+        );
+        assert_eq!(
+            check(Vec2::new(10.0, 10.0)),
+            Some(PlacementProblem::InsideStation)
+        );
+        assert_eq!(
+            check(Vec2::new(55.0, 5.0)),
+            Some(PlacementProblem::OnAsteroid)
+        );
+        assert_eq!(
+            check(Vec2::new(5.0, 52.0)),
+            Some(PlacementProblem::TooCloseToBuilding)
+        );
+    }
+}
@@ -1,8 +1,15 @@
 use bevy::{input::mouse::MouseMotion, prelude::*};
+use rand::Rng;
 
 use crate::AppSet;
 
-use super::{build::BuildLocationMarker, movement::Velocity, phase::GamePhase};
+use super::{
+    build::BuildLocationMarker,
+    input::{Action, Actions, Stick},
+    movement::Velocity,
+    phase::GamePhase,
+    rng::GameRng,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.init_resource::<CameraTarget>();
@@ -47,17 +54,31 @@ fn move_camera_to_target(
 }
 
 const PAN_SENSITIVITY: f32 = 0.1;
+/// How fast, in world units per second, the right stick pans the camera.
+const STICK_PAN_SPEED: f32 = 40.0;
 
 fn pan_camera_target(
     camera_target: Res<CameraTarget>,
     mut target_query: Query<&mut Transform>,
     mut mouse_motion: EventReader<MouseMotion>,
-    mouse_input: Res<ButtonInput<MouseButton>>,
+    actions: Actions,
     build_marker: Query<&BuildLocationMarker>,
+    time: Res<Time>,
 ) {
     let mut total_motion: Vec2 = mouse_motion.read().map(|ev| ev.delta).sum();
     total_motion.y = -total_motion.y;
 
+    // The stick doesn't share a binding with placement, so it can always pan.
+    let stick = actions.stick(Stick::Right);
+    if stick != Vec2::ZERO {
+        if let Some(target) = camera_target.0 {
+            if let Ok(mut target_transform) = target_query.get_mut(target) {
+                target_transform.translation +=
+                    (stick * STICK_PAN_SPEED * time.delta_seconds()).extend(0.0);
+            }
+        }
+    }
+
     // Hacky way to prevent panning when placing a building
     if let Some(marker) = build_marker.iter().next() {
         if marker.mode.is_some() {
@@ -65,7 +86,7 @@ fn pan_camera_target(
         }
     }
 
-    if mouse_input.pressed(MouseButton::Left) {
+    if actions.pressed(Action::Pan) {
         if let Some(target) = camera_target.0 {
             if let Ok(mut target_transform) = target_query.get_mut(target) {
                 target_transform.translation.x -= total_motion.x * PAN_SENSITIVITY;
@@ -78,9 +99,9 @@ fn pan_camera_target(
 fn reset_camera_target(
     camera_target: Res<CameraTarget>,
     mut target_query: Query<&mut Transform>,
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
 ) {
-    if input.just_pressed(KeyCode::Space) || input.just_pressed(KeyCode::KeyE) {
+    if actions.just_pressed(Action::ResetCamera) {
         if let Some(target) = camera_target.0 {
             if let Ok(mut target_transform) = target_query.get_mut(target) {
                 target_transform.translation.x = 0.0;
@@ -173,6 +194,7 @@ fn spawn_dust_particles(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     camera: Query<&Transform, With<Camera>>,
+    mut rng: ResMut<GameRng>,
 ) {
     timer.timer.tick(time.delta());
     if timer.timer.just_finished() {
@@ -190,9 +212,9 @@ fn spawn_dust_particles(
             .cached_mesh
             .get_or_insert_with(|| meshes.add(Sphere { radius: 0.1 }.mesh().ico(1).unwrap()))
             .clone();
-        let random_dx = (rand::random::<f32>() - 0.5) * 300.0;
-        let random_dy = (rand::random::<f32>() - 0.5) * 300.0;
-        let random_z = (rand::random::<f32>() - 0.5) * 50.0;
+        let random_dx = (rng.gen::<f32>() - 0.5) * 300.0;
+        let random_dy = (rng.gen::<f32>() - 0.5) * 300.0;
+        let random_z = (rng.gen::<f32>() - 0.5) * 50.0;
         let camera_transform = camera.iter().next().unwrap();
         let pos = Vec3::new(
             camera_transform.translation.x + random_dx,
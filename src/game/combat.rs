@@ -1,15 +1,25 @@
 use std::time::Duration;
 
 use avian3d::collision::contact_reporting::Collision;
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::{
+    ecs::entity::EntityHashSet,
+    prelude::*,
+    window::{CursorMoved, PrimaryWindow},
+};
+use bevy_health_bar3d::configuration::{BarHeight, BarSettings};
 
 use crate::AppSet;
 
 use super::{
     assets::SfxKey,
     audio::sfx::PlaySfx,
+    input::{Action, Actions, Stick},
     phase::GamePhase,
-    spawn::{bullet::Bullet, player::CombatShipTurret},
+    spawn::{
+        bullet::{Bullet, BulletHit, Shooter},
+        enemy::{Enemy, EnemyKilled},
+        player::CombatShipTurret,
+    },
     upgrades::Upgrades,
 };
 
@@ -45,16 +55,22 @@ pub struct CombatController {
     attack_time: f32,
     attack_timer: Timer,
     rotation_speed: f32,
+    damage: f32,
     mouse_world_pos: Vec2,
+    /// The direction last aimed with a gamepad stick. Takes priority over the
+    /// mouse until the cursor moves again.
+    stick_aim: Option<Vec2>,
     shoot: bool,
 }
 impl CombatController {
-    pub fn new(attack_time: f32, rotation_speed: f32) -> Self {
+    pub fn new(attack_time: f32, rotation_speed: f32, damage: f32) -> Self {
         Self {
             attack_time,
             attack_timer: Timer::from_seconds(attack_time, TimerMode::Once),
             rotation_speed,
+            damage,
             mouse_world_pos: Vec2::ZERO,
+            stick_aim: None,
             shoot: false,
         }
     }
@@ -67,11 +83,24 @@ fn tick_attack_timer(mut controller_query: Query<&mut CombatController>, time: R
 }
 
 fn record_combat_controller(
-    input: Res<ButtonInput<MouseButton>>,
+    actions: Actions,
     camera: Query<(&Camera, &GlobalTransform)>,
     window: Query<&Window, With<PrimaryWindow>>,
+    mut cursor_moved: EventReader<CursorMoved>,
     mut controller_query: Query<&mut CombatController>,
 ) {
+    // Record stick aim, which is kept when the stick is released so that the
+    // turret doesn't swing back to a stale cursor position.
+    let aim = actions.stick(Stick::Right);
+    let cursor_moved = cursor_moved.read().count() > 0;
+    for mut controller in &mut controller_query {
+        if aim != Vec2::ZERO {
+            controller.stick_aim = Some(aim.normalize());
+        } else if cursor_moved {
+            controller.stick_aim = None;
+        }
+    }
+
     // Record mouse world position.
     let (camera, camera_transform) = camera.single();
     if let Ok(window) = window.get_single() {
@@ -90,7 +119,7 @@ fn record_combat_controller(
 
     // Record attack input.
     for mut controller in &mut controller_query {
-        controller.shoot = input.pressed(MouseButton::Left);
+        controller.shoot = actions.pressed(Action::Fire);
     }
 }
 
@@ -103,10 +132,13 @@ fn rotate_towards_mouse(
         let mut transform = turret_query
             .get_single_mut()
             .expect("Expected single turret");
-        let target_direction = (controller.mouse_world_pos
-            - global_transform.translation().truncate())
-        .normalize_or_zero()
-        .extend(0.0);
+        let target_direction = controller
+            .stick_aim
+            .unwrap_or_else(|| {
+                (controller.mouse_world_pos - global_transform.translation().truncate())
+                    .normalize_or_zero()
+            })
+            .extend(0.0);
         let current_direction = transform.rotation * Vec3::Y;
         let rotation = current_direction.angle_between(target_direction);
         if rotation > 0.0 {
@@ -123,6 +155,8 @@ fn rotate_towards_mouse(
 pub struct ShootEvent {
     pub position: Vec3,
     pub direction: Vec3,
+    pub damage: f32,
+    pub shooter: Shooter,
 }
 
 fn shoot(
@@ -144,6 +178,8 @@ fn shoot(
                 commands.trigger(ShootEvent {
                     position,
                     direction,
+                    damage: controller.damage,
+                    shooter: Shooter::CombatShip,
                 });
                 commands.trigger(PlaySfx::Key(SfxKey::Shoot));
             }
@@ -153,14 +189,51 @@ fn shoot(
 
 fn handle_enemy_bullet_collision(
     mut collision_event_reader: EventReader<Collision>,
-    bullets: Query<Entity, With<Bullet>>,
+    bullets: Query<&Bullet>,
+    mut enemies: Query<(&mut Enemy, Has<BarSettings<Enemy>>)>,
     mut commands: Commands,
 ) {
+    // A bullet may touch several enemies at once, but should only damage one.
+    let mut spent_bullets = EntityHashSet::default();
     for Collision(contacts) in collision_event_reader.read() {
-        if bullets.contains(contacts.entity1) || bullets.contains(contacts.entity2) {
-            commands.entity(contacts.entity1).despawn_recursive();
-            commands.entity(contacts.entity2).despawn_recursive();
+        let (bullet_entity, enemy_entity) = if bullets.contains(contacts.entity1) {
+            (contacts.entity1, contacts.entity2)
+        } else if bullets.contains(contacts.entity2) {
+            (contacts.entity2, contacts.entity1)
+        } else {
+            continue;
+        };
+        let Ok(bullet) = bullets.get(bullet_entity) else {
+            continue;
+        };
+        let Ok((mut enemy, has_health_bar)) = enemies.get_mut(enemy_entity) else {
+            continue;
+        };
+        if !spent_bullets.insert(bullet_entity) {
+            continue;
+        }
+
+        let was_alive = enemy.health > 0.0;
+        enemy.health -= bullet.damage;
+        if was_alive && enemy.health <= 0.0 {
+            commands.trigger(EnemyKilled {
+                kind: enemy.kind,
+                shooter: bullet.shooter.clone(),
+            });
+        }
+        if !has_health_bar {
+            // Only show health bars on enemies that have been hit.
+            commands.entity(enemy_entity).insert(BarSettings::<Enemy> {
+                width: 3.0,
+                offset: 2.0,
+                height: BarHeight::Static(0.3),
+                ..Default::default()
+            });
         }
+        commands.trigger(BulletHit {
+            target: enemy_entity,
+        });
+        commands.entity(bullet_entity).despawn_recursive();
     }
 }
 
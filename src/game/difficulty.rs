@@ -0,0 +1,127 @@
+//! Difficulty settings chosen before starting a run.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::gameplay::ResourceBundle;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Difficulty>();
+    app.register_type::<Difficulty>();
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum DifficultyPreset {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    /// Any settings that don't match a preset.
+    Custom,
+}
+impl DifficultyPreset {
+    pub const ALL: [DifficultyPreset; 4] = [
+        DifficultyPreset::Easy,
+        DifficultyPreset::Normal,
+        DifficultyPreset::Hard,
+        DifficultyPreset::Custom,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DifficultyPreset::Easy => "Easy",
+            DifficultyPreset::Normal => "Normal",
+            DifficultyPreset::Hard => "Hard",
+            DifficultyPreset::Custom => "Custom",
+        }
+    }
+}
+
+/// The difficulty of the next run. The difficulty of the current run is kept
+/// in the [`GameplayManager`](super::gameplay::GameplayManager).
+#[derive(Resource, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct Difficulty {
+    pub preset: DifficultyPreset,
+    pub starting_resources: ResourceBundle,
+    pub station_health: f32,
+    /// How much faster enemies trickle in after each cycle.
+    pub spawn_rate_increment: f32,
+    /// How much more damage enemies deal after each cycle.
+    pub damage_increment: f32,
+    /// Multiplier on the number of enemies in each wave.
+    pub wave_size: f32,
+    /// Seconds of peace at the start of each cycle before enemies start to
+    /// trickle in.
+    pub peace_time: f32,
+}
+impl Difficulty {
+    /// The settings of a preset. Custom starts out the same as Normal.
+    pub fn preset(preset: DifficultyPreset) -> Self {
+        match preset {
+            DifficultyPreset::Easy => Self {
+                preset,
+                starting_resources: ResourceBundle {
+                    metal: 20,
+                    crystal: 8,
+                    ice: 8,
+                },
+                station_health: 3000.0,
+                spawn_rate_increment: 0.1,
+                damage_increment: 0.05,
+                wave_size: 0.75,
+                peace_time: 40.0,
+            },
+            DifficultyPreset::Normal | DifficultyPreset::Custom => Self {
+                preset,
+                starting_resources: ResourceBundle {
+                    metal: 10,
+                    crystal: 4,
+                    ice: 4,
+                },
+                station_health: 2000.0,
+                spawn_rate_increment: 0.2,
+                damage_increment: 0.1,
+                wave_size: 1.0,
+                peace_time: 30.0,
+            },
+            DifficultyPreset::Hard => Self {
+                preset,
+                starting_resources: ResourceBundle {
+                    metal: 5,
+                    crystal: 2,
+                    ice: 2,
+                },
+                station_health: 1500.0,
+                spawn_rate_increment: 0.3,
+                damage_increment: 0.15,
+                wave_size: 1.25,
+                peace_time: 20.0,
+            },
+        }
+    }
+
+    /// Scales the score, so that harder runs rank higher. Custom runs score the
+    /// same as Easy, as their settings can be made as lenient as desired.
+    pub fn score_multiplier(&self) -> f32 {
+        match self.preset {
+            DifficultyPreset::Easy | DifficultyPreset::Custom => 0.5,
+            DifficultyPreset::Normal => 1.0,
+            DifficultyPreset::Hard => 1.5,
+        }
+    }
+
+    /// The number of enemies in a group of `count` at this difficulty.
+    pub fn wave_group_size(&self, count: u32) -> u32 {
+        if count == 0 {
+            return 0;
+        }
+        ((count as f32 * self.wave_size).round() as u32).max(1)
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self::preset(DifficultyPreset::Normal)
+    }
+}
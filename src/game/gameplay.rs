@@ -1,8 +1,18 @@
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign},
+};
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{screen::Screen, AppSet};
 
-use super::spawn::enemy::SpawnEnemy;
+use super::{
+    difficulty::Difficulty,
+    spawn::enemy::{EnemyKind, SpawnEnemy},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.insert_resource(Resources::default());
@@ -21,27 +31,175 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-#[derive(Resource, Reflect, Debug)]
+/// The kinds of resource that asteroids contain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum ResourceKind {
+    #[default]
+    Metal,
+    Crystal,
+    Ice,
+}
+impl ResourceKind {
+    pub const ALL: [ResourceKind; 3] = [
+        ResourceKind::Metal,
+        ResourceKind::Crystal,
+        ResourceKind::Ice,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResourceKind::Metal => "Metal",
+            ResourceKind::Crystal => "Crystal",
+            ResourceKind::Ice => "Ice",
+        }
+    }
+
+    /// Tint of asteroids of this kind, which is also used for their waypoints
+    /// and in the UI.
+    pub fn color(&self) -> Color {
+        match self {
+            ResourceKind::Metal => Color::srgb(0.8, 0.6, 0.5),
+            ResourceKind::Crystal => Color::srgb(0.75, 0.45, 1.0),
+            ResourceKind::Ice => Color::srgb(0.55, 0.85, 1.0),
+        }
+    }
+}
+
+/// An amount of each kind of resource, such as the cost of a building. Kinds
+/// left out when deserializing are zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceBundle {
+    pub metal: u32,
+    pub crystal: u32,
+    pub ice: u32,
+}
+impl ResourceBundle {
+    /// A bundle of a single kind of resource.
+    pub fn new(kind: ResourceKind, amount: u32) -> Self {
+        let mut bundle = Self::default();
+        *bundle.get_mut(kind) = amount;
+        bundle
+    }
+
+    pub fn get(&self, kind: ResourceKind) -> u32 {
+        match kind {
+            ResourceKind::Metal => self.metal,
+            ResourceKind::Crystal => self.crystal,
+            ResourceKind::Ice => self.ice,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: ResourceKind) -> &mut u32 {
+        match kind {
+            ResourceKind::Metal => &mut self.metal,
+            ResourceKind::Crystal => &mut self.crystal,
+            ResourceKind::Ice => &mut self.ice,
+        }
+    }
+
+    /// The amount of all kinds together.
+    pub fn total(&self) -> u32 {
+        ResourceKind::ALL.iter().map(|&kind| self.get(kind)).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+
+    /// Whether there is at least as much of every kind as in `cost`.
+    pub fn covers(&self, cost: &ResourceBundle) -> bool {
+        ResourceKind::ALL
+            .iter()
+            .all(|&kind| self.get(kind) >= cost.get(kind))
+    }
+
+    /// Take away `cost` if there is enough of every kind, returning whether it
+    /// was paid.
+    pub fn spend(&mut self, cost: &ResourceBundle) -> bool {
+        if !self.covers(cost) {
+            return false;
+        }
+        for kind in ResourceKind::ALL {
+            *self.get_mut(kind) -= cost.get(kind);
+        }
+        true
+    }
+
+    /// Multiply each kind by `factor`, rounding with `round`.
+    pub fn scale(&self, factor: f32, round: fn(f32) -> f32) -> Self {
+        let mut scaled = *self;
+        for kind in ResourceKind::ALL {
+            *scaled.get_mut(kind) = round(self.get(kind) as f32 * factor) as u32;
+        }
+        scaled
+    }
+}
+
+impl Add for ResourceBundle {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl AddAssign for ResourceBundle {
+    fn add_assign(&mut self, other: Self) {
+        for kind in ResourceKind::ALL {
+            *self.get_mut(kind) += other.get(kind);
+        }
+    }
+}
+
+impl Sum for ResourceBundle {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+/// Lists the kinds present, e.g. "4 Metal, 2 Ice".
+impl fmt::Display for ResourceBundle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "nothing");
+        }
+        let parts: Vec<_> = ResourceKind::ALL
+            .iter()
+            .filter(|&&kind| self.get(kind) > 0)
+            .map(|&kind| format!("{} {}", self.get(kind), kind.label()))
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[derive(Resource, Reflect, Debug, Clone, Serialize, Deserialize)]
 #[reflect(Resource)]
 pub struct Resources {
-    pub gathered: u32,
-    pub delivered: u32,
+    /// Held by the mining ship until it reaches the station.
+    pub gathered: ResourceBundle,
+    /// Available to spend.
+    pub delivered: ResourceBundle,
 }
 impl Default for Resources {
     fn default() -> Self {
         Self {
-            gathered: 0,
-            delivered: 10,
+            gathered: ResourceBundle::default(),
+            delivered: Difficulty::default().starting_resources,
         }
     }
 }
 impl Resources {
-    pub fn reset(&mut self) {
-        *self = Self::default();
+    pub fn reset(&mut self, difficulty: &Difficulty) {
+        *self = Self {
+            delivered: difficulty.starting_resources,
+            ..Default::default()
+        };
     }
 }
 
-#[derive(Debug, Resource, Reflect)]
+#[derive(Debug, Clone, Resource, Reflect, Serialize, Deserialize)]
 #[reflect(Resource)]
 pub struct GameplayManager {
     enemy_spawn_timer: Timer,
@@ -50,32 +208,36 @@ pub struct GameplayManager {
     pub enemy_damage_multiplier: f32,
     pub elapsed_time: f32,
     pub current_phase_time: f32,
-    pub asteroid_spawn_distance: f32,
     pub cycle: u32,
+    pub difficulty: Difficulty,
 }
 impl Default for GameplayManager {
     fn default() -> Self {
+        Self::new(Difficulty::default())
+    }
+}
+impl GameplayManager {
+    pub fn new(difficulty: Difficulty) -> Self {
         Self {
             enemy_spawn_timer: Timer::from_seconds(5.0, TimerMode::Repeating),
-            peace_timer: Timer::from_seconds(30.0, TimerMode::Once),
+            peace_timer: Timer::from_seconds(difficulty.peace_time, TimerMode::Once),
             enemy_spawn_rate_multiplier: 1.0,
             enemy_damage_multiplier: 1.0,
             elapsed_time: 0.0,
             current_phase_time: 0.0,
-            asteroid_spawn_distance: 100.0,
             cycle: 0,
+            difficulty,
         }
     }
-}
-impl GameplayManager {
-    pub fn reset(&mut self) {
-        *self = Self::default();
+
+    pub fn reset(&mut self, difficulty: &Difficulty) {
+        *self = Self::new(difficulty.clone());
     }
 
     pub fn new_cycle(&mut self) {
         // Increase difficulty
-        self.enemy_spawn_rate_multiplier += 0.2;
-        self.enemy_damage_multiplier += 0.1;
+        self.enemy_spawn_rate_multiplier += self.difficulty.spawn_rate_increment;
+        self.enemy_damage_multiplier += self.difficulty.damage_increment;
         self.peace_timer.reset();
         self.cycle += 1;
     }
@@ -102,6 +264,10 @@ fn spawn_enemies(mut commands: Commands, manager: Res<GameplayManager>) {
         commands.trigger(SpawnEnemy {
             distance: ENEMY_SPAWN_DISTANCE,
             damage_mult: manager.enemy_damage_multiplier,
+            bearing: None,
+            kind: EnemyKind::Standard,
+            position: None,
+            health: None,
         });
     }
 }
@@ -0,0 +1,442 @@
+//! Player actions and the keys and buttons bound to them. Gameplay systems
+//! read [`Actions`] rather than raw keyboard, mouse or gamepad input, so that
+//! the bindings can be changed from the controls screen.
+
+use std::collections::BTreeMap;
+
+use bevy::{
+    ecs::system::SystemParam, input::InputSystem, prelude::*, ui::UiSystem, window::PrimaryWindow,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{screen::Paused, storage::Storage};
+
+use super::phase::GamePhase;
+
+/// Key of the input map in [`Storage`].
+pub const INPUT_MAP_KEY: &str = "controls";
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<InputMap>();
+    app.add_systems(Startup, load_input_map);
+    app.add_systems(
+        PreUpdate,
+        drive_virtual_cursor
+            .after(InputSystem)
+            .before(UiSystem::Focus),
+    );
+}
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect, Serialize, Deserialize,
+)]
+pub enum Action {
+    ThrustUp,
+    ThrustDown,
+    ThrustLeft,
+    ThrustRight,
+    /// Mine the asteroid below the mining ship.
+    Mine,
+    /// Scan for asteroids.
+    Scan,
+    /// Fire the combat ship's turret.
+    Fire,
+    /// Pan the camera by moving the mouse.
+    Pan,
+    ResetCamera,
+    ToggleZoom,
+    /// Place a building, or select an existing one.
+    Select,
+    CycleTargeting,
+    /// Turn snapping building placement to a grid on or off.
+    ToggleSnap,
+    /// End the Build phase early.
+    Ready,
+    /// Leave build mode, or pause the game.
+    Cancel,
+}
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::ThrustUp,
+        Action::ThrustDown,
+        Action::ThrustLeft,
+        Action::ThrustRight,
+        Action::Mine,
+        Action::Scan,
+        Action::Fire,
+        Action::Pan,
+        Action::ResetCamera,
+        Action::ToggleZoom,
+        Action::Select,
+        Action::CycleTargeting,
+        Action::ToggleSnap,
+        Action::Ready,
+        Action::Cancel,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::ThrustUp => "Thrust up",
+            Action::ThrustDown => "Thrust down",
+            Action::ThrustLeft => "Thrust left",
+            Action::ThrustRight => "Thrust right",
+            Action::Mine => "Mine",
+            Action::Scan => "Scan",
+            Action::Fire => "Fire",
+            Action::Pan => "Pan camera",
+            Action::ResetCamera => "Reset camera",
+            Action::ToggleZoom => "Toggle zoom",
+            Action::Select => "Place / select building",
+            Action::CycleTargeting => "Change turret targeting",
+            Action::ToggleSnap => "Toggle grid snapping",
+            Action::Ready => "Ready",
+            Action::Cancel => "Cancel / pause",
+        }
+    }
+}
+
+/// A key, mouse button or gamepad button that can be bound to an [`Action`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// A button on any connected gamepad.
+    Gamepad(GamepadButtonType),
+}
+impl InputBinding {
+    pub fn is_gamepad(&self) -> bool {
+        matches!(self, InputBinding::Gamepad(_))
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            InputBinding::Key(key) => {
+                let name = format!("{key:?}");
+                ["Key", "Digit", "Arrow"]
+                    .iter()
+                    .find_map(|prefix| name.strip_prefix(prefix))
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or(&name)
+                    .to_string()
+            }
+            InputBinding::Mouse(MouseButton::Left) => "Left click".to_string(),
+            InputBinding::Mouse(MouseButton::Right) => "Right click".to_string(),
+            InputBinding::Mouse(MouseButton::Middle) => "Middle click".to_string(),
+            InputBinding::Mouse(button) => format!("Mouse {button:?}"),
+            InputBinding::Gamepad(button) => format!("Pad {button:?}"),
+        }
+    }
+}
+
+/// The bindings of every action. Persisted in [`Storage`] under
+/// [`INPUT_MAP_KEY`].
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputMap {
+    pub bindings: BTreeMap<Action, Vec<InputBinding>>,
+}
+impl InputMap {
+    pub fn bindings(&self, action: Action) -> &[InputBinding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Replace the bindings of an action with a single binding. Bindings on
+    /// the other kind of device are kept, so that rebinding a key doesn't
+    /// unbind the gamepad and vice versa.
+    pub fn rebind(&mut self, action: Action, binding: InputBinding) {
+        let bindings = self.bindings.entry(action).or_default();
+        bindings.retain(|existing| existing.is_gamepad() != binding.is_gamepad());
+        bindings.push(binding);
+    }
+
+    /// The gamepad buttons bound to an action.
+    fn gamepad_buttons(&self, action: Action) -> impl Iterator<Item = GamepadButtonType> + '_ {
+        self.bindings(action)
+            .iter()
+            .filter_map(|binding| match binding {
+                InputBinding::Gamepad(button) => Some(*button),
+                _ => None,
+            })
+    }
+
+    /// The bindings of an action, for display.
+    pub fn describe(&self, action: Action) -> String {
+        let bindings = self.bindings(action);
+        if bindings.is_empty() {
+            return "Unbound".to_string();
+        }
+        bindings
+            .iter()
+            .map(InputBinding::label)
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use InputBinding::{Gamepad, Key, Mouse};
+        Self {
+            bindings: BTreeMap::from([
+                (
+                    Action::ThrustUp,
+                    vec![
+                        Key(KeyCode::KeyW),
+                        Key(KeyCode::ArrowUp),
+                        Gamepad(GamepadButtonType::DPadUp),
+                    ],
+                ),
+                (
+                    Action::ThrustDown,
+                    vec![
+                        Key(KeyCode::KeyS),
+                        Key(KeyCode::ArrowDown),
+                        Gamepad(GamepadButtonType::DPadDown),
+                    ],
+                ),
+                (
+                    Action::ThrustLeft,
+                    vec![
+                        Key(KeyCode::KeyA),
+                        Key(KeyCode::ArrowLeft),
+                        Gamepad(GamepadButtonType::DPadLeft),
+                    ],
+                ),
+                (
+                    Action::ThrustRight,
+                    vec![
+                        Key(KeyCode::KeyD),
+                        Key(KeyCode::ArrowRight),
+                        Gamepad(GamepadButtonType::DPadRight),
+                    ],
+                ),
+                (
+                    Action::Mine,
+                    vec![
+                        Key(KeyCode::Space),
+                        Key(KeyCode::KeyE),
+                        Gamepad(GamepadButtonType::LeftTrigger2),
+                    ],
+                ),
+                (
+                    Action::Scan,
+                    vec![Mouse(MouseButton::Right), Gamepad(GamepadButtonType::West)],
+                ),
+                (
+                    Action::Fire,
+                    vec![
+                        Mouse(MouseButton::Left),
+                        Gamepad(GamepadButtonType::RightTrigger2),
+                    ],
+                ),
+                (Action::Pan, vec![Mouse(MouseButton::Left)]),
+                (
+                    Action::ResetCamera,
+                    vec![
+                        Key(KeyCode::Space),
+                        Key(KeyCode::KeyE),
+                        Gamepad(GamepadButtonType::RightThumb),
+                    ],
+                ),
+                (
+                    Action::ToggleZoom,
+                    vec![Key(KeyCode::KeyZ), Gamepad(GamepadButtonType::Select)],
+                ),
+                (
+                    Action::Select,
+                    vec![Mouse(MouseButton::Left), Gamepad(GamepadButtonType::South)],
+                ),
+                (
+                    Action::CycleTargeting,
+                    vec![Key(KeyCode::KeyT), Gamepad(GamepadButtonType::North)],
+                ),
+                (
+                    Action::ToggleSnap,
+                    vec![Key(KeyCode::KeyG), Gamepad(GamepadButtonType::LeftTrigger)],
+                ),
+                (
+                    Action::Ready,
+                    vec![Key(KeyCode::Enter), Gamepad(GamepadButtonType::Start)],
+                ),
+                (
+                    Action::Cancel,
+                    vec![Key(KeyCode::Escape), Gamepad(GamepadButtonType::East)],
+                ),
+            ]),
+        }
+    }
+}
+
+/// The state of every [`Action`], according to the current [`InputMap`].
+#[derive(SystemParam)]
+pub struct Actions<'w> {
+    input_map: Res<'w, InputMap>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse_buttons: Res<'w, ButtonInput<MouseButton>>,
+    gamepads: GamepadInput<'w>,
+}
+impl Actions<'_> {
+    /// Whether any binding of the action is held down.
+    pub fn pressed(&self, action: Action) -> bool {
+        self.any(
+            action,
+            ButtonInput::pressed,
+            ButtonInput::pressed,
+            ButtonInput::pressed,
+        )
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.any(
+            action,
+            ButtonInput::just_pressed,
+            ButtonInput::just_pressed,
+            ButtonInput::just_pressed,
+        )
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.any(
+            action,
+            ButtonInput::just_released,
+            ButtonInput::just_released,
+            ButtonInput::just_released,
+        )
+    }
+
+    /// The deflection of a stick on the first gamepad that is using it.
+    pub fn stick(&self, stick: Stick) -> Vec2 {
+        self.gamepads.stick(stick)
+    }
+
+    fn any(
+        &self,
+        action: Action,
+        key_state: fn(&ButtonInput<KeyCode>, KeyCode) -> bool,
+        mouse_state: fn(&ButtonInput<MouseButton>, MouseButton) -> bool,
+        gamepad_state: fn(&ButtonInput<GamepadButton>, GamepadButton) -> bool,
+    ) -> bool {
+        self.input_map
+            .bindings(action)
+            .iter()
+            .any(|binding| match *binding {
+                InputBinding::Key(key) => key_state(&self.keys, key),
+                InputBinding::Mouse(button) => mouse_state(&self.mouse_buttons, button),
+                InputBinding::Gamepad(button) => self.gamepads.any(button, gamepad_state),
+            })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stick {
+    /// Thrust, or the cursor during the build phase.
+    Left,
+    /// Aiming, or panning the camera during the build phase.
+    Right,
+}
+
+/// Deflections smaller than this are ignored, so that worn sticks don't drift.
+const STICK_DEAD_ZONE: f32 = 0.2;
+
+/// The buttons and sticks of every connected gamepad.
+#[derive(SystemParam)]
+pub struct GamepadInput<'w> {
+    gamepads: Res<'w, Gamepads>,
+    buttons: Res<'w, ButtonInput<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+}
+impl GamepadInput<'_> {
+    /// A button that was just pressed on any gamepad, if any.
+    pub fn just_pressed_button(&self) -> Option<GamepadButtonType> {
+        self.buttons
+            .get_just_pressed()
+            .next()
+            .map(|button| button.button_type)
+    }
+
+    fn any(
+        &self,
+        button: GamepadButtonType,
+        state: fn(&ButtonInput<GamepadButton>, GamepadButton) -> bool,
+    ) -> bool {
+        self.gamepads
+            .iter()
+            .any(|gamepad| state(&self.buttons, GamepadButton::new(gamepad, button)))
+    }
+
+    fn stick(&self, stick: Stick) -> Vec2 {
+        let (x, y) = match stick {
+            Stick::Left => (GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY),
+            Stick::Right => (GamepadAxisType::RightStickX, GamepadAxisType::RightStickY),
+        };
+        self.gamepads
+            .iter()
+            .map(|gamepad| {
+                let axis = |axis_type| {
+                    self.axes
+                        .get(GamepadAxis::new(gamepad, axis_type))
+                        .unwrap_or(0.0)
+                };
+                Vec2::new(axis(x), axis(y))
+            })
+            .find(|deflection| deflection.length() > STICK_DEAD_ZONE)
+            .map_or(Vec2::ZERO, |deflection| deflection.clamp_length_max(1.0))
+    }
+}
+
+/// How fast, in logical pixels per second, the left stick moves the cursor.
+const VIRTUAL_CURSOR_SPEED: f32 = 800.0;
+
+/// Let a gamepad stand in for the mouse while building or paused: the left
+/// stick moves the cursor and the [`Action::Select`] gamepad button clicks, so
+/// that placement, selection and the menus all work without a mouse.
+fn drive_virtual_cursor(
+    input_map: Res<InputMap>,
+    gamepads: GamepadInput,
+    mut mouse_buttons: ResMut<ButtonInput<MouseButton>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    phase: Option<Res<State<GamePhase>>>,
+    paused: Option<Res<State<Paused>>>,
+    time: Res<Time<Real>>,
+    mut pressed_left: Local<bool>,
+) {
+    for button in input_map.gamepad_buttons(Action::Select) {
+        // Release even outside the phase, in case it ended while the button was
+        // held, but leave a real mouse button alone.
+        if *pressed_left && gamepads.any(button, ButtonInput::just_released) {
+            mouse_buttons.release(MouseButton::Left);
+            *pressed_left = false;
+        }
+    }
+    let building = phase.is_some_and(|phase| *phase.get() == GamePhase::Build);
+    let paused = paused.is_some_and(|paused| paused.0);
+    if !building && !paused {
+        return;
+    }
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+
+    let deflection = gamepads.stick(Stick::Left);
+    if deflection != Vec2::ZERO {
+        let size = window.size();
+        let position = window.cursor_position().unwrap_or(size / 2.0);
+        // Screen coordinates grow downwards.
+        let motion = Vec2::new(deflection.x, -deflection.y);
+        let position = (position + motion * VIRTUAL_CURSOR_SPEED * time.delta_seconds())
+            .clamp(Vec2::ZERO, size);
+        window.set_cursor_position(Some(position));
+    }
+
+    for button in input_map.gamepad_buttons(Action::Select) {
+        if gamepads.any(button, ButtonInput::just_pressed) {
+            mouse_buttons.press(MouseButton::Left);
+            *pressed_left = true;
+        }
+    }
+}
+
+fn load_input_map(mut commands: Commands, storage: Res<Storage>) {
+    if let Some(input_map) = storage.load::<InputMap>(INPUT_MAP_KEY) {
+        commands.insert_resource(input_map);
+    }
+}
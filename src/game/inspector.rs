@@ -0,0 +1,420 @@
+//! Inspect, repair and sell existing buildings during the Build phase.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    screen::Screen,
+    ui::{interaction::InteractionQuery, prelude::*},
+    AppSet,
+};
+
+use super::{
+    assets::SfxKey,
+    audio::sfx::PlaySfx,
+    build::{BuildLocationMarker, SELECT_RADIUS},
+    gameplay::{ResourceBundle, Resources},
+    input::{Action, Actions},
+    notifications::Notification,
+    phase::GamePhase,
+    power::{NeedsPower, Powered},
+    spawn::{
+        building::{Building, BuildingCatalogue, Destructable, UpgradeBuilding},
+        station::Station,
+    },
+    turret::Turret,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SelectedBuilding>();
+    app.observe(repair_building);
+    app.observe(sell_building);
+    app.add_systems(OnExit(GamePhase::Build), clear_selection);
+    app.add_systems(
+        Update,
+        select_building
+            .run_if(in_state(GamePhase::Build))
+            .in_set(AppSet::RecordInput),
+    );
+    app.add_systems(
+        Update,
+        (handle_inspector_action, spawn_inspector, update_inspector)
+            .chain()
+            .run_if(in_state(Screen::Playing))
+            .in_set(AppSet::Update),
+    );
+}
+
+/// The building shown in the inspector, if any.
+#[derive(Resource, Debug, Default)]
+pub struct SelectedBuilding(pub Option<Entity>);
+
+/// Repair a building to full health, if its cost can be paid.
+#[derive(Event, Debug)]
+pub struct RepairBuilding(pub Entity);
+
+/// Demolish a building for a partial refund.
+#[derive(Event, Debug)]
+pub struct SellBuilding(pub Entity);
+
+/// The cost of repairing the station from zero to full health. Other buildings
+/// use everything spent on building and upgrading them.
+const STATION_REPAIR_COST: ResourceBundle = ResourceBundle {
+    metal: 30,
+    crystal: 0,
+    ice: 10,
+};
+/// Fraction of the build cost that a repair from zero health costs.
+const REPAIR_COST_FACTOR: f32 = 0.5;
+/// Fraction of the build cost refunded when selling an undamaged building.
+const SELL_REFUND_FACTOR: f32 = 0.5;
+/// How far, in pixels, the cursor can move between press and release for it
+/// to still count as a click rather than a camera pan.
+const CLICK_DRAG_THRESHOLD: f32 = 5.0;
+/// How close the cursor needs to be to the centre of the station to select it.
+const STATION_SELECT_RADIUS: f32 = 10.0;
+
+fn base_cost(
+    building: Option<&Building>,
+    is_station: bool,
+    catalogue: &BuildingCatalogue,
+) -> Option<ResourceBundle> {
+    if is_station {
+        return Some(STATION_REPAIR_COST);
+    }
+    let building = building?;
+    catalogue
+        .get(&building.building_type)
+        .map(|definition| definition.total_cost(building.level))
+}
+
+fn repair_cost(destructable: &Destructable, base_cost: &ResourceBundle) -> ResourceBundle {
+    let missing = 1.0 - destructable.health / destructable.max_health();
+    base_cost.scale(REPAIR_COST_FACTOR * missing, f32::ceil)
+}
+
+fn sell_refund(destructable: &Destructable, base_cost: &ResourceBundle) -> ResourceBundle {
+    let remaining = destructable.health / destructable.max_health();
+    base_cost.scale(SELL_REFUND_FACTOR * remaining, f32::floor)
+}
+
+fn repair_building(
+    trigger: Trigger<RepairBuilding>,
+    mut commands: Commands,
+    mut building_query: Query<(&mut Destructable, Option<&Building>, Has<Station>)>,
+    catalogue: Res<BuildingCatalogue>,
+    mut resources: ResMut<Resources>,
+    mut notification_writer: EventWriter<Notification>,
+) {
+    let Ok((mut destructable, building, is_station)) = building_query.get_mut(trigger.event().0)
+    else {
+        return;
+    };
+    if destructable.health >= destructable.max_health() {
+        notification_writer.send(Notification("Already at full health".to_string()));
+        return;
+    }
+    let Some(base_cost) = base_cost(building, is_station, &catalogue) else {
+        return;
+    };
+    let cost = repair_cost(&destructable, &base_cost);
+    if !resources.delivered.spend(&cost) {
+        notification_writer.send(Notification(format!("Not enough resources - needs {cost}")));
+        return;
+    }
+    destructable.health = destructable.max_health();
+    commands.trigger(PlaySfx::Key(SfxKey::Build));
+}
+
+fn sell_building(
+    trigger: Trigger<SellBuilding>,
+    mut commands: Commands,
+    building_query: Query<(&Destructable, &Building, &Name), Without<Station>>,
+    catalogue: Res<BuildingCatalogue>,
+    mut resources: ResMut<Resources>,
+    mut notification_writer: EventWriter<Notification>,
+) {
+    let entity = trigger.event().0;
+    let Ok((destructable, building, name)) = building_query.get(entity) else {
+        return;
+    };
+    let Some(base_cost) = base_cost(Some(building), false, &catalogue) else {
+        return;
+    };
+    let refund = sell_refund(destructable, &base_cost);
+    resources.delivered += refund;
+    notification_writer.send(Notification(format!(
+        "{} sold for {}",
+        name.as_str(),
+        refund
+    )));
+    // Any upgrade provided by the building is dropped when the power grid
+    // recounts upgrades after it despawns
+    commands.entity(entity).despawn_recursive();
+}
+
+fn select_building(
+    actions: Actions,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    interaction_query: Query<&Interaction>,
+    marker_query: Query<&BuildLocationMarker>,
+    building_query: Query<(Entity, &Transform, Has<Station>), With<Destructable>>,
+    mut press_position: Local<Option<Vec2>>,
+    mut selected: ResMut<SelectedBuilding>,
+) {
+    let cursor_position = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    if actions.just_pressed(Action::Select) {
+        *press_position = cursor_position;
+    }
+    if !actions.just_released(Action::Select) {
+        return;
+    }
+    let (Some(pressed), Some(released)) = (press_position.take(), cursor_position) else {
+        return;
+    };
+    if pressed.distance(released) > CLICK_DRAG_THRESHOLD {
+        // The camera was panned
+        return;
+    }
+    if interaction_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        // The click was on the UI
+        return;
+    }
+    let Ok(marker) = marker_query.get_single() else {
+        return;
+    };
+    if marker.mode.is_some() {
+        // The click places a new building
+        return;
+    }
+
+    let mouse_world_pos = marker.mouse_world_pos();
+    selected.0 = building_query
+        .iter()
+        .map(|(entity, transform, is_station)| {
+            let radius = if is_station {
+                STATION_SELECT_RADIUS
+            } else {
+                SELECT_RADIUS
+            };
+            let distance = transform.translation.xy().distance(mouse_world_pos);
+            (entity, distance, radius)
+        })
+        .filter(|(_, distance, radius)| distance < radius)
+        .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b))
+        .map(|(entity, _, _)| entity);
+}
+
+fn clear_selection(mut selected: ResMut<SelectedBuilding>) {
+    selected.0 = None;
+}
+
+#[derive(Component)]
+struct InspectorUi;
+
+#[derive(Component)]
+struct InspectorText;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum InspectorAction {
+    Repair,
+    Upgrade,
+    Sell,
+    CycleTargeting,
+    Close,
+}
+
+fn spawn_inspector(
+    mut commands: Commands,
+    selected: Res<SelectedBuilding>,
+    inspector_query: Query<Entity, With<InspectorUi>>,
+    building_query: Query<
+        (&Name, Option<&Building>, Has<Station>, Has<Turret>),
+        With<Destructable>,
+    >,
+    catalogue: Res<BuildingCatalogue>,
+) {
+    if !selected.is_changed() {
+        return;
+    }
+    for entity in inspector_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let Some((name, building, is_station, is_turret)) = selected
+        .0
+        .and_then(|entity| building_query.get(entity).ok())
+    else {
+        return;
+    };
+    let can_upgrade = building.is_some_and(|building| {
+        catalogue
+            .get(&building.building_type)
+            .and_then(|definition| definition.upgrade_cost(building.level))
+            .is_some()
+    });
+
+    let style = TextStyle {
+        font_size: 20.0,
+        color: ui_palette::LABEL_TEXT,
+        ..Default::default()
+    };
+    commands
+        .spawn((
+            Name::new("Inspector"),
+            InspectorUi,
+            StateScoped(Screen::Playing),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(160.0),
+                    right: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..Default::default()
+                },
+                background_color: BackgroundColor(ui_palette::NODE_BACKGROUND),
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                name.as_str(),
+                TextStyle {
+                    font_size: 28.0,
+                    color: ui_palette::HEADER_TEXT,
+                    ..Default::default()
+                },
+            ));
+            parent.spawn((
+                InspectorText,
+                TextBundle::from_sections([
+                    TextSection::from_style(style.clone()),
+                    TextSection::from_style(style.clone()),
+                    TextSection::from_style(style.clone()),
+                    TextSection::from_style(style.clone()),
+                    TextSection::from_style(style.clone()),
+                    TextSection::from_style(style.clone()),
+                    TextSection::from_style(style),
+                ]),
+            ));
+            parent
+                .small_button("Repair")
+                .insert(InspectorAction::Repair);
+            if can_upgrade {
+                parent
+                    .small_button("Upgrade")
+                    .insert(InspectorAction::Upgrade);
+            }
+            if !is_station {
+                parent.small_button("Sell").insert(InspectorAction::Sell);
+            }
+            if is_turret {
+                parent
+                    .small_button("Change targeting")
+                    .insert(InspectorAction::CycleTargeting);
+            }
+            parent.small_button("Close").insert(InspectorAction::Close);
+        });
+}
+
+fn update_inspector(
+    mut selected: ResMut<SelectedBuilding>,
+    mut text_query: Query<&mut Text, With<InspectorText>>,
+    building_query: Query<(
+        &Destructable,
+        Option<&Building>,
+        Has<Station>,
+        Option<&Turret>,
+        Has<NeedsPower>,
+        Has<Powered>,
+    )>,
+    catalogue: Res<BuildingCatalogue>,
+) {
+    let Some(entity) = selected.0 else {
+        return;
+    };
+    let Ok((destructable, building, is_station, turret, needs_power, powered)) =
+        building_query.get(entity)
+    else {
+        // The building was destroyed or sold
+        selected.0 = None;
+        return;
+    };
+    let base_cost = base_cost(building, is_station, &catalogue).unwrap_or_default();
+    let definition = building.and_then(|building| catalogue.get(&building.building_type));
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = match (building, definition) {
+            (Some(building), Some(definition)) if definition.max_level() > 0 => format!(
+                "Level: {} / {}\n",
+                building.level + 1,
+                definition.max_level() + 1
+            ),
+            _ => String::new(),
+        };
+        text.sections[1].value = format!(
+            "Health: {:.0} / {:.0}\n",
+            destructable.health.max(0.0),
+            destructable.max_health()
+        );
+        text.sections[2].value =
+            format!("Repair cost: {}\n", repair_cost(destructable, &base_cost));
+        text.sections[3].value = building
+            .zip(definition)
+            .and_then(|(building, definition)| definition.upgrade_cost(building.level))
+            .map(|cost| format!("Upgrade cost: {cost}\n"))
+            .unwrap_or_default();
+        text.sections[4].value = if is_station {
+            String::new()
+        } else {
+            format!("Sell value: {}\n", sell_refund(destructable, &base_cost))
+        };
+        text.sections[5].value = turret
+            .map(|turret| format!("Targeting: {}\n", turret.targeting.label()))
+            .unwrap_or_default();
+        text.sections[6].value = if needs_power && !powered {
+            "No power - out of reach of the station and pylons".to_string()
+        } else {
+            String::new()
+        };
+    }
+}
+
+fn handle_inspector_action(
+    mut commands: Commands,
+    mut button_query: InteractionQuery<&InspectorAction>,
+    mut selected: ResMut<SelectedBuilding>,
+    mut turret_query: Query<&mut Turret>,
+) {
+    let Some(entity) = selected.0 else {
+        return;
+    };
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                InspectorAction::Repair => commands.trigger(RepairBuilding(entity)),
+                InspectorAction::Upgrade => {
+                    commands.trigger(UpgradeBuilding(entity));
+                    // Rebuild the inspector, hiding the button at the last level
+                    selected.set_changed();
+                }
+                InspectorAction::Sell => {
+                    commands.trigger(SellBuilding(entity));
+                    selected.0 = None;
+                }
+                InspectorAction::CycleTargeting => {
+                    if let Ok(mut turret) = turret_query.get_mut(entity) {
+                        turret.targeting = turret.targeting.next();
+                    }
+                }
+                InspectorAction::Close => selected.0 = None,
+            }
+        }
+    }
+}
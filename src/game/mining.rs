@@ -9,9 +9,15 @@ use super::{
     audio::sfx::PlaySfx,
     collision::CollisionLayer,
     gameplay::Resources,
+    input::{Action, Actions, InputMap},
+    movement::MovementController,
     notifications::Notification,
     phase::GamePhase,
-    spawn::asteroid::{Asteroid, SpawnRandomAsteroid, ASTEROID_WAYPOINT_COLOR},
+    spawn::{
+        asteroid::{nearest_asteroids_report, Asteroid},
+        asteroid_field::SpawnRandomAsteroid,
+    },
+    stats::RunStats,
     upgrades::Upgrades,
     waypoint::Waypointed,
 };
@@ -38,13 +44,37 @@ pub(super) fn plugin(app: &mut App) {
             .run_if(in_state(GamePhase::Gather))
             .in_set(AppSet::Update),
     );
-    app.add_systems(Update, deliver_resources.in_set(AppSet::Update));
+    app.add_systems(
+        Update,
+        (deliver_resources, apply_cargo_load).in_set(AppSet::Update),
+    );
     app.add_systems(
         Update,
         update_mining_speed_mult.run_if(resource_changed::<Upgrades>),
     );
 }
 
+/// How many resources the mining ship can hold without upgrades.
+const BASE_CARGO_CAPACITY: u32 = 10;
+/// Extra room in the hold from each cargo upgrade.
+const CARGO_CAPACITY_PER_UPGRADE: u32 = 5;
+/// Above this fraction of its capacity, the mining ship starts to slow down.
+const HEAVY_LOAD: f32 = 0.5;
+/// Speed of the mining ship with a full hold, relative to an empty one.
+const FULL_LOAD_SPEED: f32 = 0.6;
+
+/// How many resources the mining ship can hold before delivering them.
+pub fn cargo_capacity(upgrades: &Upgrades) -> u32 {
+    BASE_CARGO_CAPACITY + CARGO_CAPACITY_PER_UPGRADE * upgrades.cargo_capacity as u32
+}
+
+/// Speed of the mining ship relative to an empty hold.
+fn load_speed_multiplier(gathered: u32, capacity: u32) -> f32 {
+    let load = gathered as f32 / capacity.max(1) as f32;
+    let heaviness = ((load - HEAVY_LOAD) / (1.0 - HEAVY_LOAD)).clamp(0.0, 1.0);
+    1.0 - (1.0 - FULL_LOAD_SPEED) * heaviness
+}
+
 #[derive(Component, Reflect, Default)]
 #[reflect(Component)]
 pub struct MiningController {
@@ -67,14 +97,13 @@ impl MiningController {
 }
 
 fn record_interaction_controller(
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
     mut controller_query: Query<&mut MiningController>,
 ) {
     for mut controller in controller_query.iter_mut() {
-        controller.interacting = input.pressed(KeyCode::KeyE) || input.pressed(KeyCode::Space);
-        controller.just_interacted =
-            input.just_pressed(KeyCode::KeyE) || input.just_pressed(KeyCode::Space);
-        if input.just_released(KeyCode::KeyE) || input.just_released(KeyCode::Space) {
+        controller.interacting = actions.pressed(Action::Mine);
+        controller.just_interacted = actions.just_pressed(Action::Mine);
+        if actions.just_released(Action::Mine) {
             controller.timer.reset();
         }
     }
@@ -105,28 +134,46 @@ fn mine(
     mut asteroid_query: Query<&mut Asteroid>,
     time: Res<Time>,
     mut resources: ResMut<Resources>,
+    upgrades: Res<Upgrades>,
+    mut stats: ResMut<RunStats>,
     spatial_query: SpatialQuery,
     mut commands: Commands,
+    mut notification_writer: EventWriter<Notification>,
 ) {
+    let capacity = cargo_capacity(&upgrades);
+    let hold_full =
+        || Notification("Cargo hold full. Return to the station to deliver.".to_string());
     for (transform, mut controller) in query.iter_mut() {
-        if controller.interacting {
-            if let Some(hit) = spatial_query.cast_ray(
-                transform.translation,
-                Dir3::NEG_Z,
-                100.0,
-                false,
-                SpatialQueryFilter::from_mask(CollisionLayer::Asteroid),
-            ) {
-                let speed_multiplier = controller.mining_speed_multiplier;
-                controller
-                    .timer
-                    .tick(time.delta().mul_f32(speed_multiplier));
-                if controller.timer.finished() {
-                    resources.gathered += 1;
-                    if let Ok(mut asteroid) = asteroid_query.get_mut(hit.entity) {
-                        asteroid.contained_resources -= 1;
-                    }
-                    commands.trigger(PlaySfx::Key(SfxKey::Collect));
+        if !controller.interacting {
+            continue;
+        }
+        if resources.gathered.total() >= capacity {
+            if controller.just_interacted {
+                notification_writer.send(hold_full());
+            }
+            continue;
+        }
+        if let Some(hit) = spatial_query.cast_ray(
+            transform.translation,
+            Dir3::NEG_Z,
+            100.0,
+            false,
+            SpatialQueryFilter::from_mask(CollisionLayer::Asteroid),
+        ) {
+            let speed_multiplier = controller.mining_speed_multiplier;
+            controller
+                .timer
+                .tick(time.delta().mul_f32(speed_multiplier));
+            if controller.timer.finished() {
+                let Ok(mut asteroid) = asteroid_query.get_mut(hit.entity) else {
+                    continue;
+                };
+                asteroid.contained_resources -= 1;
+                *resources.gathered.get_mut(asteroid.kind) += 1;
+                stats.current().resources_mined += 1;
+                commands.trigger(PlaySfx::Key(SfxKey::Collect));
+                if resources.gathered.total() >= capacity {
+                    notification_writer.send(hold_full());
                 }
             }
         }
@@ -144,26 +191,41 @@ fn update_mining_speed_mult(
 
 fn deliver_resources(
     mut resources: ResMut<Resources>,
+    mut stats: ResMut<RunStats>,
     mut query: Query<&Transform, With<MiningController>>,
     mut commands: Commands,
     mut notification_writer: EventWriter<Notification>,
 ) {
     // If within 15m of the station (origin), deliver resources
     for transform in query.iter_mut() {
-        if transform.translation.xy().length() < 25.0 && resources.gathered > 0 {
-            resources.delivered += resources.gathered;
-            notification_writer.send(Notification(format!(
-                "Delivered {} resources to the base.",
-                resources.gathered
-            )));
-            resources.gathered = 0;
+        if transform.translation.xy().length() < 25.0 && !resources.gathered.is_empty() {
+            let gathered = std::mem::take(&mut resources.gathered);
+            resources.delivered += gathered;
+            stats.current().resources_delivered += gathered.total();
+            notification_writer.send(Notification(format!("Delivered {gathered} to the base.")));
             commands.trigger(PlaySfx::Key(SfxKey::Collect));
         }
     }
 }
 
+/// Slow the mining ship down as its hold fills up.
+fn apply_cargo_load(
+    mut controller_query: Query<&mut MovementController, With<MiningController>>,
+    resources: Res<Resources>,
+    upgrades: Res<Upgrades>,
+) {
+    let multiplier = load_speed_multiplier(resources.gathered.total(), cargo_capacity(&upgrades));
+    for mut controller in &mut controller_query {
+        if controller.speed_multiplier != multiplier {
+            controller.speed_multiplier = multiplier;
+        }
+    }
+}
+
 fn destroy_empty_asteroids(
     mut commands: Commands,
+    input_map: Res<InputMap>,
+    mut stats: ResMut<RunStats>,
     query: Query<(Entity, &Asteroid, &Visibility)>,
     mut notification_writer: EventWriter<Notification>,
 ) {
@@ -173,38 +235,31 @@ fn destroy_empty_asteroids(
         if asteroid.contained_resources == 0 {
             commands.entity(entity).despawn_recursive();
             commands.trigger(SpawnRandomAsteroid);
+            stats.current().asteroids_depleted += 1;
             despawned = true;
         } else if matches!(visibility, Visibility::Visible) {
             are_visible_astroids = true;
         }
     }
     if !are_visible_astroids && despawned {
-        notification_writer.send(Notification(
-            "All known asteroids have been mined. Use right-click to locate more asteroids."
-                .to_string(),
-        ));
+        notification_writer.send(Notification(format!(
+            "All known asteroids have been mined. Use {} to locate more asteroids.",
+            input_map.describe(Action::Scan)
+        )));
     }
 }
 
 fn scan(
-    mouse_input: Res<ButtonInput<MouseButton>>,
+    actions: Actions,
     ship_query: Query<&Transform, With<MiningController>>,
-    asteroids_query: Query<&Transform, With<Asteroid>>,
+    asteroids_query: Query<(&Asteroid, &Transform)>,
     mut notification_writer: EventWriter<Notification>,
 ) {
-    if mouse_input.just_pressed(MouseButton::Right) {
+    if actions.just_pressed(Action::Scan) {
         for ship_transform in ship_query.iter() {
-            let scan_pos = ship_transform.translation;
-            let mut nearest_distance = f32::MAX;
-            for transform in asteroids_query.iter() {
-                let distance = transform.translation.xy().distance(scan_pos.xy());
-                if distance < nearest_distance {
-                    nearest_distance = distance;
-                }
-            }
-            notification_writer.send(Notification(format!(
-                "Nearest asteroid: {:.2} units away",
-                nearest_distance,
+            notification_writer.send(Notification(nearest_asteroids_report(
+                ship_transform.translation.xy(),
+                &asteroids_query,
             )));
         }
     }
@@ -212,12 +267,12 @@ fn scan(
 
 fn reveal_nearby_asteroids(
     ship_query: Query<&Transform, With<MiningController>>,
-    asteroids_query: Query<(Entity, &Transform, &Visibility), With<Asteroid>>,
+    asteroids_query: Query<(Entity, &Asteroid, &Transform, &Visibility)>,
     mut notification_writer: EventWriter<Notification>,
     mut commands: Commands,
 ) {
     for ship_transform in ship_query.iter() {
-        for (entity, asteroid_transform, visability) in asteroids_query.iter() {
+        for (entity, asteroid, asteroid_transform, visability) in asteroids_query.iter() {
             if matches!(visability, Visibility::Hidden)
                 && ship_transform
                     .translation
@@ -225,12 +280,36 @@ fn reveal_nearby_asteroids(
                     .distance_squared(asteroid_transform.translation.xy())
                     < 500.0
             {
-                notification_writer.send(Notification("Asteroid detected".to_string()));
+                notification_writer.send(Notification(format!(
+                    "{} asteroid detected",
+                    asteroid.kind.label()
+                )));
                 commands
                     .entity(entity)
                     .insert(Visibility::Visible)
-                    .insert(Waypointed::new(ASTEROID_WAYPOINT_COLOR));
+                    .insert(Waypointed::new(asteroid.kind.color()));
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrades_enlarge_the_hold_and_loads_slow_the_ship() {
+        let base = cargo_capacity(&Upgrades::default());
+        let capacity = cargo_capacity(&Upgrades {
+            cargo_capacity: 1,
+            ..Default::default()
+        });
+        assert!(capacity > base);
+
+        assert_eq!(load_speed_multiplier(0, capacity), 1.0);
+        assert_eq!(load_speed_multiplier(capacity / 2, capacity), 1.0);
+        let full = load_speed_multiplier(capacity, capacity);
+        assert!(full < load_speed_multiplier(capacity * 3 / 4, capacity));
+        assert!(full > 0.0);
+    }
+}
@@ -6,7 +6,7 @@ use bevy_health_bar3d::plugin::HealthBarPlugin;
 
 use self::{
     mining::InteractionProgressBar,
-    spawn::{asteroid::Asteroid, building::Destructable},
+    spawn::{asteroid::Asteroid, building::Destructable, enemy::Enemy},
 };
 
 pub mod assets;
@@ -15,26 +15,53 @@ pub mod build;
 pub mod camera;
 pub mod collision;
 mod combat;
+pub mod difficulty;
 pub mod gameplay;
+pub mod input;
+pub mod inspector;
 mod mining;
 mod movement;
 pub mod notifications;
 pub mod phase;
+pub mod power;
+pub mod rng;
+pub mod save;
+pub mod score;
 pub mod spawn;
+pub mod stats;
 pub mod turret;
 pub mod ui;
 pub mod upgrades;
 pub mod util;
+pub mod wave;
 pub mod waypoint;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
-        PhysicsPlugins::default(),
         HealthBarPlugin::<Destructable>::default(),
         HealthBarPlugin::<InteractionProgressBar>::default(),
         HealthBarPlugin::<Asteroid>::default(),
-        notifications::plugin,
+        HealthBarPlugin::<Enemy>::default(),
         audio::plugin,
+        headless_plugin,
+    ));
+}
+
+/// The game simulation without any of the plugins that need a renderer or
+/// audio output.
+pub(super) fn headless_plugin(app: &mut App) {
+    app.add_plugins((
+        PhysicsPlugins::default(),
+        audio::headless_plugin,
+        notifications::plugin,
+        rng::plugin,
+        wave::plugin,
+        save::plugin,
+        inspector::plugin,
+        difficulty::plugin,
+        stats::plugin,
+        input::plugin,
+        power::plugin,
     ));
     app.add_plugins((
         assets::plugin,
@@ -4,6 +4,8 @@ use bevy::prelude::*;
 
 use crate::AppSet;
 
+use super::input::{Action, Actions, Stick};
+
 pub(super) fn plugin(app: &mut App) {
     // Record directional input as movement controls.
     app.register_type::<MovementController>();
@@ -28,6 +30,9 @@ pub struct MovementController {
     thrust_multiplier: f32,
     friction: f32,
     velocity_limit: f32,
+    /// Scales both thrust and the velocity limit, for example when the ship
+    /// is weighed down.
+    pub speed_multiplier: f32,
     thrust: Vec2,
     pub enabled: bool,
 }
@@ -37,33 +42,39 @@ impl MovementController {
             thrust_multiplier,
             friction,
             velocity_limit,
+            speed_multiplier: 1.0,
             ..Default::default()
         }
     }
 }
 
 fn record_movement_controller(
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
     mut controller_query: Query<&mut MovementController>,
 ) {
     // Collect directional input.
     let mut intent = Vec2::ZERO;
-    if input.pressed(KeyCode::KeyW) || input.pressed(KeyCode::ArrowUp) {
+    if actions.pressed(Action::ThrustUp) {
         intent.y += 1.0;
     }
-    if input.pressed(KeyCode::KeyS) || input.pressed(KeyCode::ArrowDown) {
+    if actions.pressed(Action::ThrustDown) {
         intent.y -= 1.0;
     }
-    if input.pressed(KeyCode::KeyA) || input.pressed(KeyCode::ArrowLeft) {
+    if actions.pressed(Action::ThrustLeft) {
         intent.x -= 1.0;
     }
-    if input.pressed(KeyCode::KeyD) || input.pressed(KeyCode::ArrowRight) {
+    if actions.pressed(Action::ThrustRight) {
         intent.x += 1.0;
     }
 
     // Normalize so that diagonal movement has the same speed as
-    // horizontal and vertical movement.
-    let intent = intent.normalize_or_zero();
+    // horizontal and vertical movement. Without directional input, fall back
+    // to the left stick, which keeps its analog strength.
+    let intent = if intent == Vec2::ZERO {
+        actions.stick(Stick::Left)
+    } else {
+        intent.normalize()
+    };
 
     // Apply movement intent to controllers.
     for mut controller in &mut controller_query {
@@ -85,11 +96,15 @@ fn update_velocity(mut query: Query<(&MovementController, &mut Velocity)>, time:
         }
 
         // Apply thrust.
-        velocity.0 += controller.thrust * controller.thrust_multiplier * time.delta_seconds();
+        velocity.0 += controller.thrust
+            * controller.thrust_multiplier
+            * controller.speed_multiplier
+            * time.delta_seconds();
 
         // Limit max velocity.
-        if velocity.0.length() > controller.velocity_limit {
-            velocity.0 = velocity.0.normalize() * controller.velocity_limit;
+        let velocity_limit = controller.velocity_limit * controller.speed_multiplier;
+        if velocity.0.length() > velocity_limit {
+            velocity.0 = velocity.0.normalize() * velocity_limit;
         }
     }
 }
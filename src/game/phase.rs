@@ -1,26 +1,38 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    game::{assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack, spawn::enemy::SpawnEnemy},
+    game::{assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack},
     screen::Screen,
     AppSet,
 };
 
 use super::{
     camera::CameraTarget,
-    gameplay::GameplayManager,
+    gameplay::{GameplayManager, ResourceBundle, ResourceKind, Resources},
     movement::MovementController,
+    notifications::Notification,
     spawn::{
+        asteroid_field::ReplenishAsteroidField,
         player::{CombatShip, CombatShipCameraTarget, MiningShipCameraTarget},
         station::StationCameraTarget,
     },
     ui::{BuildUi, CombatUi, GatherUi},
+    wave::StartWave,
 };
 
-pub const PHASE_DURATION: f32 = 24.0;
+const BUILD_DURATION: f32 = 20.0;
+const GATHER_DURATION: f32 = 24.0;
+const COMBAT_BASE_DURATION: f32 = 24.0;
+/// Extra Combat time per cycle, giving the growing waves time to play out.
+const COMBAT_DURATION_PER_CYCLE: f32 = 3.0;
+const COMBAT_MAX_DURATION: f32 = 48.0;
+/// Metal granted for each second of the Build phase skipped by readying up.
+const READY_BONUS_PER_SECOND: f32 = 0.25;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_sub_state::<GamePhase>();
+    app.observe(skip_build_phase);
     app.add_systems(OnEnter(GamePhase::Gather), on_gather);
     app.add_systems(OnExit(GamePhase::Gather), exit_gather);
     app.add_systems(OnEnter(GamePhase::Combat), on_combat);
@@ -35,7 +47,7 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-#[derive(SubStates, Clone, Eq, PartialEq, Debug, Hash, Default)]
+#[derive(SubStates, Clone, Eq, PartialEq, Debug, Hash, Default, Serialize, Deserialize)]
 #[source(Screen = Screen::Playing)]
 pub enum GamePhase {
     Gather,
@@ -44,6 +56,83 @@ pub enum GamePhase {
     Build,
 }
 
+impl GamePhase {
+    /// The phases of a cycle, in the order they are played.
+    pub const CYCLE: [GamePhase; 3] = [GamePhase::Build, GamePhase::Gather, GamePhase::Combat];
+
+    /// Position of the phase within a cycle.
+    pub fn index(&self) -> usize {
+        match self {
+            GamePhase::Build => 0,
+            GamePhase::Gather => 1,
+            GamePhase::Combat => 2,
+        }
+    }
+}
+
+/// How long `phase` lasts during `cycle`, in seconds.
+pub fn phase_duration(phase: &GamePhase, cycle: u32) -> f32 {
+    match phase {
+        GamePhase::Build => BUILD_DURATION,
+        GamePhase::Gather => GATHER_DURATION,
+        GamePhase::Combat => (COMBAT_BASE_DURATION + COMBAT_DURATION_PER_CYCLE * cycle as f32)
+            .min(COMBAT_MAX_DURATION),
+    }
+}
+
+/// How long all phases of `cycle` last together, in seconds.
+pub fn cycle_duration(cycle: u32) -> f32 {
+    GamePhase::CYCLE
+        .iter()
+        .map(|phase| phase_duration(phase, cycle))
+        .sum()
+}
+
+/// The number of cycles that fit into `elapsed_time`, including the completed
+/// fraction of the last one.
+pub fn cycles_elapsed(mut elapsed_time: f32) -> f32 {
+    let mut cycle = 0;
+    loop {
+        let duration = cycle_duration(cycle);
+        if elapsed_time < duration {
+            return cycle as f32 + elapsed_time / duration;
+        }
+        elapsed_time -= duration;
+        cycle += 1;
+    }
+}
+
+/// Trigger this event to end the Build phase early.
+#[derive(Event, Debug)]
+pub struct SkipBuildPhase;
+
+/// Fast-forward the phase clock to the end of the Build phase, so that
+/// [`update_phase`] moves on to Gather and the spinner stays in step.
+fn skip_build_phase(
+    _trigger: Trigger<SkipBuildPhase>,
+    phase: Option<Res<State<GamePhase>>>,
+    mut manager: ResMut<GameplayManager>,
+    mut resources: ResMut<Resources>,
+    mut notification_writer: EventWriter<Notification>,
+) {
+    if !phase.is_some_and(|phase| *phase.get() == GamePhase::Build) {
+        return;
+    }
+    let remaining =
+        (phase_duration(&GamePhase::Build, manager.cycle) - manager.current_phase_time).max(0.0);
+    manager.current_phase_time += remaining;
+    manager.elapsed_time += remaining;
+
+    let bonus = ResourceBundle::new(
+        ResourceKind::Metal,
+        (remaining * READY_BONUS_PER_SECOND) as u32,
+    );
+    if !bonus.is_empty() {
+        resources.delivered += bonus;
+        notification_writer.send(Notification(format!("+{bonus} for readying up early")));
+    }
+}
+
 fn on_gather(
     mut camera_target: ResMut<CameraTarget>,
     camera_target_query: Query<Entity, With<MiningShipCameraTarget>>,
@@ -125,22 +214,19 @@ fn update_phase(
     mut manager: ResMut<GameplayManager>,
     mut commands: Commands,
 ) {
-    if manager.current_phase_time > PHASE_DURATION {
+    let duration = phase_duration(current_state.get(), manager.cycle);
+    if manager.current_phase_time >= duration {
         match current_state.get() {
             GamePhase::Build => next_state.set(GamePhase::Gather),
             GamePhase::Gather => {
-                for _ in 0..5 + (manager.cycle as f32 * 2.5) as u32 {
-                    commands.trigger(SpawnEnemy {
-                        distance: 100.0,
-                        damage_mult: manager.enemy_damage_multiplier,
-                    });
-                }
                 manager.stop_spawning();
+                commands.trigger(StartWave);
                 next_state.set(GamePhase::Combat);
             }
             GamePhase::Combat => {
                 // A full cycle has passed. Increase the difficulty
                 manager.new_cycle();
+                commands.trigger(ReplenishAsteroidField);
                 if manager.cycle % 2 == 0 {
                     // Just in case the soundtrack has desynced
                     commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Gameplay));
@@ -148,6 +234,25 @@ fn update_phase(
                 next_state.set(GamePhase::Build);
             }
         }
-        manager.current_phase_time -= PHASE_DURATION;
+        manager.current_phase_time -= duration;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combat_lasts_longer_in_later_cycles() {
+        let first = phase_duration(&GamePhase::Combat, 0);
+        let later = phase_duration(&GamePhase::Combat, 4);
+        assert!(later > first);
+        assert_eq!(
+            phase_duration(&GamePhase::Build, 0),
+            phase_duration(&GamePhase::Build, 4)
+        );
+
+        let elapsed = cycle_duration(0) + cycle_duration(1) / 2.0;
+        assert!((cycles_elapsed(elapsed) - 1.5).abs() < 1e-4);
     }
 }
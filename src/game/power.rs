@@ -0,0 +1,148 @@
+//! The power grid. The station and pylons project power over a radius, and
+//! pylons pass it on to each other. Turrets and upgrade buildings only work
+//! while they are within reach of the grid.
+
+use bevy::prelude::*;
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    notifications::Notification,
+    phase::GamePhase,
+    spawn::building::StatusLight,
+    upgrades::{Upgrade, UpgradeType, Upgrades},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PowerSource>();
+    app.register_type::<NeedsPower>();
+    app.register_type::<Powered>();
+    app.add_systems(
+        Update,
+        (update_power_grid, (count_upgrades, update_status_lights))
+            .chain()
+            .run_if(in_state(Screen::Playing))
+            .in_set(AppSet::PostUpdate),
+    );
+}
+
+/// Powers everything within `radius`. Only sources connected to the station,
+/// directly or through other sources, are live.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct PowerSource {
+    pub radius: f32,
+    /// The root of the grid.
+    pub is_station: bool,
+}
+
+/// A building that only works while powered.
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct NeedsPower;
+
+/// Added to power sources connected to the grid and to buildings within its
+/// reach.
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct Powered;
+
+/// Walk the grid outwards from the station, then power every building in
+/// reach of a live source.
+fn update_power_grid(
+    mut commands: Commands,
+    source_query: Query<(Entity, &PowerSource, &Transform, Has<Powered>)>,
+    consumer_query: Query<
+        (Entity, &Transform, Has<Powered>),
+        (With<NeedsPower>, Without<PowerSource>),
+    >,
+    phase: Option<Res<State<GamePhase>>>,
+    mut notification_writer: EventWriter<Notification>,
+) {
+    let sources: Vec<_> = source_query
+        .iter()
+        .map(|(entity, source, transform, was_powered)| {
+            (entity, *source, transform.translation.xy(), was_powered)
+        })
+        .collect();
+    let mut live: Vec<bool> = sources
+        .iter()
+        .map(|(_, source, _, _)| source.is_station)
+        .collect();
+    let mut frontier: Vec<usize> = (0..sources.len()).filter(|&i| live[i]).collect();
+    while let Some(i) = frontier.pop() {
+        let (_, source, position, _) = sources[i];
+        for (j, (_, _, other, _)) in sources.iter().enumerate() {
+            if !live[j] && position.distance(*other) <= source.radius {
+                live[j] = true;
+                frontier.push(j);
+            }
+        }
+    }
+
+    let in_reach = |position: Vec2| {
+        sources
+            .iter()
+            .zip(&live)
+            .any(|((_, source, source_position, _), &live)| {
+                live && source_position.distance(position) <= source.radius
+            })
+    };
+    let mut lost_power = 0;
+    let mut set_powered = |entity: Entity, was_powered: bool, powered: bool| {
+        if powered && !was_powered {
+            commands.entity(entity).insert(Powered);
+        } else if !powered && was_powered {
+            commands.entity(entity).remove::<Powered>();
+            lost_power += 1;
+        }
+    };
+    for (&(entity, _, _, was_powered), &live) in sources.iter().zip(&live) {
+        set_powered(entity, was_powered, live);
+    }
+    for (entity, transform, was_powered) in &consumer_query {
+        set_powered(entity, was_powered, in_reach(transform.translation.xy()));
+    }
+
+    // Losing a pylon in combat can take a whole branch of the grid with it.
+    if lost_power > 0 && phase.is_some_and(|phase| *phase.get() == GamePhase::Combat) {
+        notification_writer.send(Notification(format!("{lost_power} building(s) lost power")));
+    }
+}
+
+/// Only powered upgrade buildings count.
+fn count_upgrades(upgrade_query: Query<&Upgrade, With<Powered>>, mut upgrades: ResMut<Upgrades>) {
+    let mut counted = Upgrades::default();
+    for upgrade in &upgrade_query {
+        let count = match upgrade.upgrade_type {
+            UpgradeType::MiningSpeed => &mut counted.mining_speed,
+            UpgradeType::FireRate => &mut counted.fire_rate,
+            UpgradeType::CargoCapacity => &mut counted.cargo_capacity,
+        };
+        *count += upgrade.strength;
+    }
+    // Only write on change, since ships recalculate their stats when it does.
+    if *upgrades != counted {
+        *upgrades = counted;
+    }
+}
+
+/// Turn off the status light of buildings without power.
+fn update_status_lights(
+    building_query: Query<(&Children, Has<Powered>), With<NeedsPower>>,
+    mut light_query: Query<&mut Visibility, With<StatusLight>>,
+) {
+    for (children, powered) in &building_query {
+        let visibility = if powered {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        let mut lights = light_query.iter_many_mut(children);
+        while let Some(mut light) = lights.fetch_next() {
+            if *light != visibility {
+                *light = visibility;
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! A single seedable source of randomness for gameplay, so that a run can be
+//! replayed exactly from its seed.
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(GameRng::from_seed(0));
+}
+
+/// The random number generator that all gameplay systems should draw from.
+/// It is reseeded at the start of every run.
+#[derive(Resource, Debug)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+    /// If set, the next run will use this seed instead of a random one.
+    pub replay_seed: Option<u64>,
+}
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            replay_seed: None,
+        }
+    }
+
+    /// The seed of the current run.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Reseed for a new run, using [`Self::replay_seed`] if one was requested.
+    pub fn start_run(&mut self) {
+        let seed = self.replay_seed.take().unwrap_or_else(rand::random);
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
@@ -0,0 +1,245 @@
+//! Save the current run so that it can be continued from the title screen.
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{screen::Screen, storage::Storage};
+
+use super::{
+    gameplay::{GameplayManager, ResourceKind, Resources},
+    movement::Velocity,
+    phase::GamePhase,
+    rng::GameRng,
+    spawn::{
+        asteroid::Asteroid,
+        building::{Building, BuildingType, Destructable},
+        enemy::{Enemy, EnemyKind},
+        player::{CombatShip, MiningShip},
+        station::Station,
+    },
+    stats::RunStats,
+    turret::{TargetingPolicy, Turret},
+    wave::{EnemyGroup, PendingGroups},
+};
+
+/// Storage key of the saved run.
+pub const SAVE_KEY: &str = "run";
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(save_run);
+    app.add_systems(OnEnter(GamePhase::Build), autosave);
+    app.add_systems(OnEnter(Screen::GameOver), delete_save);
+    app.add_systems(
+        Update,
+        finish_resume.run_if(in_state(Screen::Playing).and_then(resource_exists::<Resume>)),
+    );
+    app.add_systems(Last, save_on_exit.run_if(in_state(Screen::Playing)));
+}
+
+/// Everything needed to rebuild a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedRun {
+    pub seed: u64,
+    pub phase: GamePhase,
+    pub manager: GameplayManager,
+    pub resources: Resources,
+    pub station_health: f32,
+    pub buildings: Vec<SavedBuilding>,
+    pub asteroids: Vec<SavedAsteroid>,
+    pub combat_ship: SavedShip,
+    pub mining_ship: SavedShip,
+    pub stats: RunStats,
+    /// Enemies on the field, and the groups of the wave still to arrive, so
+    /// that a run saved during Combat carries on where it left off.
+    pub enemies: Vec<SavedEnemy>,
+    pub pending_groups: Vec<EnemyGroup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedBuilding {
+    pub building_type: BuildingType,
+    pub position: Vec3,
+    pub health: f32,
+    pub level: u32,
+    /// Only turrets have a targeting policy.
+    pub targeting: Option<TargetingPolicy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedAsteroid {
+    pub position: Vec3,
+    pub kind: ResourceKind,
+    pub radius: f32,
+    /// Resources in the asteroid when full.
+    pub max_resources: u32,
+    pub contained_resources: u32,
+    pub is_visible: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedEnemy {
+    pub kind: EnemyKind,
+    pub position: Vec3,
+    pub health: f32,
+    pub damage_mult: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedShip {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub velocity: Vec2,
+}
+
+/// When present while entering [`Screen::Playing`], the level is rebuilt from
+/// this saved run instead of starting a new one.
+#[derive(Resource, Debug)]
+pub struct Resume(pub SavedRun);
+
+/// Write the current run to storage.
+#[derive(Event, Debug)]
+pub struct SaveRun;
+
+fn save_run(
+    _trigger: Trigger<SaveRun>,
+    storage: Res<Storage>,
+    rng: Res<GameRng>,
+    phase: Option<Res<State<GamePhase>>>,
+    manager: Res<GameplayManager>,
+    resources: Res<Resources>,
+    stats: Res<RunStats>,
+    station_query: Query<&Destructable, With<Station>>,
+    building_query: Query<(&Building, &Transform, &Destructable, Option<&Turret>)>,
+    asteroid_query: Query<(&Asteroid, &Transform, &Visibility)>,
+    enemy_query: Query<(&Enemy, &Transform)>,
+    pending: Res<PendingGroups>,
+    combat_ship_query: Query<(&Transform, &Velocity), With<CombatShip>>,
+    mining_ship_query: Query<(&Transform, &Velocity), With<MiningShip>>,
+) {
+    let (Some(phase), Ok(station), Ok(combat_ship), Ok(mining_ship)) = (
+        phase,
+        station_query.get_single(),
+        combat_ship_query.get_single(),
+        mining_ship_query.get_single(),
+    ) else {
+        // There is no run in progress
+        return;
+    };
+    let saved_ship = |(transform, velocity): (&Transform, &Velocity)| SavedShip {
+        translation: transform.translation,
+        rotation: transform.rotation,
+        velocity: velocity.0,
+    };
+
+    let saved_run = SavedRun {
+        seed: rng.seed(),
+        phase: phase.get().clone(),
+        manager: manager.clone(),
+        resources: resources.clone(),
+        station_health: station.health,
+        buildings: building_query
+            .iter()
+            .filter(|(_, _, destructable, _)| destructable.health > 0.0)
+            .map(
+                |(building, transform, destructable, turret)| SavedBuilding {
+                    building_type: building.building_type.clone(),
+                    position: transform.translation,
+                    health: destructable.health,
+                    level: building.level,
+                    targeting: turret.map(|turret| turret.targeting),
+                },
+            )
+            .collect(),
+        asteroids: asteroid_query
+            .iter()
+            .map(|(asteroid, transform, visibility)| SavedAsteroid {
+                position: transform.translation,
+                kind: asteroid.kind,
+                radius: asteroid.radius,
+                max_resources: asteroid.max_resources(),
+                contained_resources: asteroid.contained_resources,
+                is_visible: matches!(visibility, Visibility::Visible),
+            })
+            .collect(),
+        combat_ship: saved_ship(combat_ship),
+        mining_ship: saved_ship(mining_ship),
+        stats: stats.clone(),
+        enemies: enemy_query
+            .iter()
+            .filter(|(enemy, _)| enemy.health > 0.0)
+            .map(|(enemy, transform)| SavedEnemy {
+                kind: enemy.kind,
+                position: transform.translation,
+                health: enemy.health,
+                damage_mult: enemy.damage_mult(),
+            })
+            .collect(),
+        pending_groups: pending.0.clone(),
+    };
+    storage.save(SAVE_KEY, &saved_run);
+}
+
+fn autosave(mut commands: Commands, manager: Res<GameplayManager>) {
+    // The first build phase of a run has nothing worth saving yet
+    if manager.cycle > 0 {
+        commands.trigger(SaveRun);
+    }
+}
+
+fn save_on_exit(mut commands: Commands, mut exit_events: EventReader<AppExit>) {
+    if exit_events.read().next().is_some() {
+        commands.trigger(SaveRun);
+    }
+}
+
+fn delete_save(storage: Res<Storage>) {
+    storage.remove(SAVE_KEY);
+}
+
+/// Restore the parts of a saved run that the spawn observers don't handle,
+/// once the level has been spawned.
+fn finish_resume(
+    mut commands: Commands,
+    resume: Res<Resume>,
+    mut manager: ResMut<GameplayManager>,
+    mut resources: ResMut<Resources>,
+    mut stats: ResMut<RunStats>,
+    mut pending: ResMut<PendingGroups>,
+    phase: Res<State<GamePhase>>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    mut station_query: Query<&mut Destructable, With<Station>>,
+    mut combat_ship_query: Query<(&mut Transform, &mut Velocity), With<CombatShip>>,
+    mut mining_ship_query: Query<
+        (&mut Transform, &mut Velocity),
+        (With<MiningShip>, Without<CombatShip>),
+    >,
+) {
+    let saved_run = &resume.0;
+    *manager = saved_run.manager.clone();
+    *resources = saved_run.resources.clone();
+    *stats = saved_run.stats.clone();
+    pending.0 = saved_run.pending_groups.clone();
+    if *phase.get() != saved_run.phase {
+        next_phase.set(saved_run.phase.clone());
+    }
+
+    // The station was spawned for the difficulty of the next run
+    for mut station in station_query.iter_mut() {
+        *station = Destructable::new(saved_run.manager.difficulty.station_health);
+        station.health = saved_run.station_health;
+    }
+    let restore_ship = |(mut transform, mut velocity): (Mut<Transform>, Mut<Velocity>),
+                        saved_ship: &SavedShip| {
+        transform.translation = saved_ship.translation;
+        transform.rotation = saved_ship.rotation;
+        velocity.0 = saved_ship.velocity;
+    };
+    for ship in combat_ship_query.iter_mut() {
+        restore_ship(ship, &saved_run.combat_ship);
+    }
+    for ship in mining_ship_query.iter_mut() {
+        restore_ship(ship, &saved_run.mining_ship);
+    }
+
+    commands.remove_resource::<Resume>();
+}
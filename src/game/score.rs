@@ -0,0 +1,111 @@
+//! The score of a run and the local table of the best runs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::Storage;
+
+use super::{
+    difficulty::DifficultyPreset, gameplay::GameplayManager, phase::cycles_elapsed, stats::RunStats,
+};
+
+/// Key of the leaderboard in [`Storage`](crate::storage::Storage).
+pub const LEADERBOARD_KEY: &str = "leaderboard";
+/// The number of runs kept on the leaderboard.
+pub const LEADERBOARD_SIZE: usize = 10;
+
+const POINTS_PER_CYCLE: f32 = 1000.0;
+const POINTS_PER_KILL: u32 = 10;
+const POINTS_PER_RESOURCE: u32 = 5;
+const POINTS_PER_BUILDING: u32 = 50;
+
+/// The points earned by a run so far, including the time survived.
+pub fn points(stats: &RunStats, manager: &GameplayManager) -> u32 {
+    let total = stats.total();
+    let base = cycles_elapsed(manager.elapsed_time) * POINTS_PER_CYCLE
+        + (total.total_kills() * POINTS_PER_KILL
+            + total.resources_delivered * POINTS_PER_RESOURCE
+            + stats.buildings_standing * POINTS_PER_BUILDING) as f32;
+    (base * manager.difficulty.score_multiplier()).round() as u32
+}
+
+/// A finished run on the leaderboard.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub points: u32,
+    pub cycles: f32,
+    pub kills: u32,
+    pub seed: u64,
+    pub difficulty: DifficultyPreset,
+    /// When the run ended, in seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+impl LeaderboardEntry {
+    /// The day the run ended, as `YYYY-MM-DD` in UTC.
+    pub fn date(&self) -> String {
+        // Days to civil date, from Howard Hinnant's `civil_from_days`
+        let days = (self.timestamp / 86_400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+}
+
+/// The best runs played on this machine, best first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+impl Leaderboard {
+    /// Add a run, returning its position if it made it onto the leaderboard.
+    pub fn insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let position = self
+            .entries
+            .iter()
+            .position(|other| other.points < entry.points)
+            .unwrap_or(self.entries.len());
+        if position >= LEADERBOARD_SIZE {
+            return None;
+        }
+        self.entries.insert(position, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(position)
+    }
+
+    /// Add a run to the stored leaderboard, returning its position if it made
+    /// it on.
+    pub fn record(storage: &Storage, entry: LeaderboardEntry) -> Option<usize> {
+        let mut leaderboard = storage
+            .load::<Leaderboard>(LEADERBOARD_KEY)
+            .unwrap_or_default();
+        let position = leaderboard.insert(entry)?;
+        storage.save(LEADERBOARD_KEY, &leaderboard);
+        Some(position)
+    }
+}
+
+/// Seconds since the Unix epoch. The web has no leaderboard, as storage is
+/// disabled there, so the time isn't needed.
+pub fn unix_time() -> u64 {
+    #[cfg(not(target_family = "wasm"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    }
+    #[cfg(target_family = "wasm")]
+    {
+        0
+    }
+}
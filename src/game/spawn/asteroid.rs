@@ -1,5 +1,5 @@
 use avian3d::collision::{Collider, CollisionLayers, LayerMask};
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use bevy_health_bar3d::configuration::{
     BarHeight, BarSettings, ColorScheme, ForegroundColor, Percentage,
 };
@@ -9,7 +9,8 @@ use crate::{
     game::{
         assets::{HandleMap, ObjectKey},
         collision::CollisionLayer,
-        gameplay::GameplayManager,
+        gameplay::ResourceKind,
+        rng::GameRng,
         util::Spin,
         waypoint::Waypointed,
     },
@@ -18,7 +19,7 @@ use crate::{
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_asteroid);
-    app.observe(spawn_random_asteroid);
+    app.add_systems(Update, tint_asteroids.run_if(in_state(Screen::Playing)));
     app.insert_resource(
         ColorScheme::<Asteroid>::new().foreground_color(ForegroundColor::Static(
             bevy::color::palettes::basic::SILVER.into(),
@@ -29,47 +30,96 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Event, Debug)]
 pub struct SpawnAsteroid {
     pub position: Vec3,
+    pub kind: ResourceKind,
+    /// Radius of the collider. The model is scaled to match.
+    pub radius: f32,
+    /// Resources in the asteroid when full.
+    pub max_resources: u32,
     pub is_visible: bool,
+    /// Resources left in the asteroid, e.g. when continuing a saved run.
+    /// Defaults to a full asteroid.
+    pub contained_resources: Option<u32>,
 }
 
 #[derive(Component, Debug, Default, Reflect)]
 pub struct Asteroid {
+    pub kind: ResourceKind,
+    pub radius: f32,
     pub contained_resources: u32,
     max_resources: u32,
 }
+impl Asteroid {
+    pub fn max_resources(&self) -> u32 {
+        self.max_resources
+    }
+}
+
 impl Percentage for Asteroid {
     fn value(&self) -> f32 {
         self.contained_resources as f32 / self.max_resources as f32
     }
 }
 
-pub const ASTEROID_WAYPOINT_COLOR: Color = Color::srgb(0.8, 0.6, 0.5);
+/// How many resources a full asteroid of each kind contains at
+/// [`ASTEROID_RADIUS`]. The rarer kinds run out sooner.
+fn base_yield(kind: ResourceKind) -> u32 {
+    match kind {
+        ResourceKind::Metal => 16,
+        ResourceKind::Crystal => 8,
+        ResourceKind::Ice => 12,
+    }
+}
+
+/// How many resources a full asteroid contains. The yield grows with the
+/// asteroid's size and the `richness` of the belt it formed in.
+pub fn asteroid_yield(kind: ResourceKind, radius: f32, richness: f32) -> u32 {
+    let size = radius / ASTEROID_RADIUS;
+    let amount = base_yield(kind) as f32 * size * size * richness;
+    (amount.round() as u32).max(1)
+}
+
+/// Pick the kind of a new asteroid. Metal is the most common and crystal the
+/// rarest.
+pub fn random_kind(rng: &mut impl Rng) -> ResourceKind {
+    match rng.gen_range(0..10) {
+        0..=4 => ResourceKind::Metal,
+        5..=6 => ResourceKind::Crystal,
+        _ => ResourceKind::Ice,
+    }
+}
+
+/// Radius of an average asteroid, which the model is built at.
+pub const ASTEROID_RADIUS: f32 = 7.0;
 
 fn spawn_asteroid(
     trigger: Trigger<SpawnAsteroid>,
     mut commands: Commands,
     object_handles: Res<HandleMap<ObjectKey>>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let mut rng = rand::thread_rng();
     let mut random_rotation = Quat::IDENTITY;
     random_rotation *= Quat::from_rotation_x(f32::to_radians(rng.gen_range(0.0..360.0)));
     random_rotation *= Quat::from_rotation_y(f32::to_radians(rng.gen_range(0.0..360.0)));
     random_rotation *= Quat::from_rotation_z(f32::to_radians(rng.gen_range(0.0..360.0)));
+    let event = trigger.event();
     let transform = Transform {
-        translation: trigger.event().position,
+        translation: event.position,
         rotation: random_rotation,
-        ..Default::default()
+        scale: Vec3::splat(event.radius / ASTEROID_RADIUS),
     };
-    let visibility = if trigger.event().is_visible {
+    let visibility = if event.is_visible {
         Visibility::Visible
     } else {
         Visibility::Hidden
     };
+    let kind = event.kind;
     let mut entity = commands.spawn((
-        Name::new("Asteroid"),
+        Name::new(format!("{} asteroid", kind.label())),
         Asteroid {
-            contained_resources: 16,
-            max_resources: 16,
+            kind,
+            radius: event.radius,
+            contained_resources: event.contained_resources.unwrap_or(event.max_resources),
+            max_resources: event.max_resources,
         },
         SceneBundle {
             scene: object_handles[&ObjectKey::Asteroid].clone_weak(),
@@ -86,7 +136,8 @@ fn spawn_asteroid(
             .normalize(),
             rotation_speed: 0.1,
         },
-        Collider::sphere(7.0),
+        // Scaled up or down to the asteroid's radius with the model.
+        Collider::sphere(ASTEROID_RADIUS),
         CollisionLayers::new([CollisionLayer::Asteroid], LayerMask::NONE),
         BarSettings::<Asteroid> {
             width: 5.0,
@@ -96,29 +147,56 @@ fn spawn_asteroid(
         },
         StateScoped(Screen::Playing),
     ));
-    if trigger.event().is_visible {
-        entity.insert(Waypointed::new(ASTEROID_WAYPOINT_COLOR));
+    if event.is_visible {
+        entity.insert(Waypointed::new(kind.color()));
     }
 }
 
-#[derive(Event, Debug)]
-pub struct SpawnRandomAsteroid;
-
-pub fn spawn_random_asteroid(
-    _trigger: Trigger<SpawnRandomAsteroid>,
+/// Tint the meshes of each asteroid's model by its kind once they have
+/// spawned. Tinted materials are shared between asteroids of the same kind.
+fn tint_asteroids(
     mut commands: Commands,
-    mut gameplay_manager: ResMut<GameplayManager>,
+    mesh_query: Query<(Entity, &Handle<StandardMaterial>), Added<Handle<StandardMaterial>>>,
+    parent_query: Query<&Parent>,
+    asteroid_query: Query<&Asteroid>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tinted: Local<HashMap<(AssetId<StandardMaterial>, ResourceKind), Handle<StandardMaterial>>>,
 ) {
-    let mut rng = rand::thread_rng();
-    let random_angle = rng.gen::<f32>() * std::f32::consts::PI * 2.0;
-    let position = Vec3::new(
-        random_angle.cos() * gameplay_manager.asteroid_spawn_distance,
-        random_angle.sin() * gameplay_manager.asteroid_spawn_distance,
-        -5.0,
-    );
-    gameplay_manager.asteroid_spawn_distance += 5.0;
-    commands.trigger(SpawnAsteroid {
-        position,
-        is_visible: false,
-    });
+    for (entity, material) in &mesh_query {
+        let Some(asteroid) = parent_query
+            .iter_ancestors(entity)
+            .find_map(|ancestor| asteroid_query.get(ancestor).ok())
+        else {
+            continue;
+        };
+        let tinted_material = tinted
+            .entry((material.id(), asteroid.kind))
+            .or_insert_with(|| {
+                let mut tinted_material = materials.get(material).cloned().unwrap_or_default();
+                tinted_material.base_color = asteroid.kind.color();
+                materials.add(tinted_material)
+            });
+        commands.entity(entity).insert(tinted_material.clone());
+    }
+}
+
+/// Describe how far the nearest asteroid of each kind is from `position`.
+pub fn nearest_asteroids_report<'a>(
+    position: Vec2,
+    asteroids: impl IntoIterator<Item = (&'a Asteroid, &'a Transform)>,
+) -> String {
+    let mut nearest: HashMap<ResourceKind, f32> = HashMap::default();
+    for (asteroid, transform) in asteroids {
+        let distance = transform.translation.xy().distance(position);
+        let nearest_distance = nearest.entry(asteroid.kind).or_insert(f32::MAX);
+        *nearest_distance = nearest_distance.min(distance);
+    }
+    let parts: Vec<_> = ResourceKind::ALL
+        .iter()
+        .map(|kind| match nearest.get(kind) {
+            Some(distance) => format!("{} {distance:.0} units", kind.label()),
+            None => format!("{} none found", kind.label()),
+        })
+        .collect();
+    format!("Nearest asteroids: {}", parts.join(", "))
 }
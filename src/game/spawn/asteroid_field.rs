@@ -0,0 +1,202 @@
+//! Procedurally generated asteroid belts. Every run scatters its belts around
+//! the station from the run's seed, and asteroids form in clusters within
+//! them. Belts further from the station are richer.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::game::{gameplay::ResourceKind, rng::GameRng};
+
+use super::asteroid::{asteroid_yield, random_kind, Asteroid, SpawnAsteroid, ASTEROID_RADIUS};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<AsteroidField>();
+    app.observe(spawn_asteroid_field);
+    app.observe(spawn_random_asteroid);
+    app.observe(replenish_asteroid_field);
+}
+
+/// The most asteroids that can exist at once.
+pub const MAX_ASTEROIDS: usize = 30;
+const BELT_COUNT: usize = 6;
+/// Distance of the centre of the nearest belt from the station.
+const BELT_MIN_DISTANCE: f32 = 70.0;
+/// Distance of the centre of the farthest belt from the station.
+const BELT_MAX_DISTANCE: f32 = 250.0;
+const BELT_MIN_SPREAD: f32 = 15.0;
+const BELT_MAX_SPREAD: f32 = 35.0;
+/// Yield multiplier of the farthest belt. Nearer belts scale down to 1.
+const MAX_RICHNESS: f32 = 1.75;
+/// Chance of an asteroid being its belt's main kind rather than a random one.
+const MAIN_KIND_CHANCE: f64 = 0.7;
+/// Range of asteroid sizes, relative to [`ASTEROID_RADIUS`].
+const MIN_SIZE: f32 = 0.7;
+const MAX_SIZE: f32 = 1.4;
+const ASTEROIDS_PER_BELT: usize = 3;
+/// Asteroids that drift into the belts at the start of each cycle.
+const ASTEROIDS_PER_CYCLE: usize = 3;
+/// Attempts at finding a spot that doesn't overlap another asteroid before
+/// settling for one that does.
+const PLACEMENT_ATTEMPTS: usize = 8;
+
+/// The belts of the current run.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct AsteroidField {
+    pub belts: Vec<AsteroidBelt>,
+}
+impl AsteroidField {
+    pub fn generate(rng: &mut impl Rng) -> Self {
+        let belts = (0..BELT_COUNT)
+            .map(|i| {
+                // Spread the belts from near to far, so that every run has both
+                let t = (i as f32 + rng.gen::<f32>()) / BELT_COUNT as f32;
+                let distance = BELT_MIN_DISTANCE + (BELT_MAX_DISTANCE - BELT_MIN_DISTANCE) * t;
+                AsteroidBelt {
+                    center: Vec2::from_angle(rng.gen_range(0.0..TAU)) * distance,
+                    spread: rng.gen_range(BELT_MIN_SPREAD..BELT_MAX_SPREAD),
+                    kind: random_kind(rng),
+                    richness: 1.0 + (MAX_RICHNESS - 1.0) * t,
+                }
+            })
+            .collect();
+        Self { belts }
+    }
+}
+
+/// A cluster of asteroids.
+#[derive(Debug, Clone)]
+pub struct AsteroidBelt {
+    pub center: Vec2,
+    /// How far from the centre asteroids can form.
+    pub spread: f32,
+    /// Most asteroids in the belt are of this kind.
+    pub kind: ResourceKind,
+    /// Multiplier on the yield of asteroids in the belt.
+    pub richness: f32,
+}
+impl AsteroidBelt {
+    /// A new, hidden asteroid somewhere in the belt. Overlapping any of
+    /// `others`, given as positions and radii, is avoided where possible.
+    fn asteroid(&self, rng: &mut impl Rng, others: &[(Vec2, f32)]) -> SpawnAsteroid {
+        let kind = if rng.gen_bool(MAIN_KIND_CHANCE) {
+            self.kind
+        } else {
+            random_kind(rng)
+        };
+        let radius = ASTEROID_RADIUS * rng.gen_range(MIN_SIZE..MAX_SIZE);
+        let mut position = self.center;
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            // The square root spreads asteroids evenly over the belt's area
+            let offset = self.spread * rng.gen::<f32>().sqrt();
+            position = self.center + Vec2::from_angle(rng.gen_range(0.0..TAU)) * offset;
+            if others
+                .iter()
+                .all(|&(other, other_radius)| other.distance(position) > radius + other_radius)
+            {
+                break;
+            }
+        }
+        SpawnAsteroid {
+            position: position.extend(-5.0),
+            kind,
+            radius,
+            max_resources: asteroid_yield(kind, radius, self.richness),
+            is_visible: false,
+            contained_resources: None,
+        }
+    }
+}
+
+/// Generate the belts of a new or resumed run from its seed. Only new runs
+/// are populated, as resumed runs restore their saved asteroids.
+#[derive(Event, Debug)]
+pub struct SpawnAsteroidField {
+    pub populate: bool,
+}
+
+fn spawn_asteroid_field(
+    trigger: Trigger<SpawnAsteroidField>,
+    mut commands: Commands,
+    mut field: ResMut<AsteroidField>,
+    mut rng: ResMut<GameRng>,
+    asteroid_query: Query<(&Asteroid, &Transform)>,
+) {
+    *field = AsteroidField::generate(&mut *rng);
+    if !trigger.event().populate {
+        return;
+    }
+    let mut placed = asteroid_positions(&asteroid_query);
+    // A couple of asteroids in plain sight of the station to get started
+    for (position, kind) in [
+        (Vec2::new(-40.0, -15.0), ResourceKind::Metal),
+        (Vec2::new(60.0, -50.0), ResourceKind::Ice),
+    ] {
+        placed.push((position, ASTEROID_RADIUS));
+        commands.trigger(SpawnAsteroid {
+            position: position.extend(-5.0),
+            kind,
+            radius: ASTEROID_RADIUS,
+            max_resources: asteroid_yield(kind, ASTEROID_RADIUS, 1.0),
+            is_visible: true,
+            contained_resources: None,
+        });
+    }
+    for belt in &field.belts {
+        for _ in 0..ASTEROIDS_PER_BELT {
+            let asteroid = belt.asteroid(&mut *rng, &placed);
+            placed.push((asteroid.position.xy(), asteroid.radius));
+            commands.trigger(asteroid);
+        }
+    }
+}
+
+/// Spawn an asteroid in a random belt, unless the field is already full.
+#[derive(Event, Debug)]
+pub struct SpawnRandomAsteroid;
+
+fn spawn_random_asteroid(
+    _trigger: Trigger<SpawnRandomAsteroid>,
+    mut commands: Commands,
+    field: Res<AsteroidField>,
+    mut rng: ResMut<GameRng>,
+    asteroid_query: Query<(&Asteroid, &Transform)>,
+) {
+    if field.belts.is_empty() || asteroid_query.iter().count() >= MAX_ASTEROIDS {
+        return;
+    }
+    let belt = &field.belts[rng.gen_range(0..field.belts.len())];
+    commands.trigger(belt.asteroid(&mut *rng, &asteroid_positions(&asteroid_query)));
+}
+
+/// Top up the belts with a few new asteroids, up to [`MAX_ASTEROIDS`].
+#[derive(Event, Debug)]
+pub struct ReplenishAsteroidField;
+
+fn replenish_asteroid_field(
+    _trigger: Trigger<ReplenishAsteroidField>,
+    mut commands: Commands,
+    field: Res<AsteroidField>,
+    mut rng: ResMut<GameRng>,
+    asteroid_query: Query<(&Asteroid, &Transform)>,
+) {
+    if field.belts.is_empty() {
+        return;
+    }
+    let mut placed = asteroid_positions(&asteroid_query);
+    let room = MAX_ASTEROIDS.saturating_sub(placed.len());
+    for _ in 0..ASTEROIDS_PER_CYCLE.min(room) {
+        let belt = &field.belts[rng.gen_range(0..field.belts.len())];
+        let asteroid = belt.asteroid(&mut *rng, &placed);
+        placed.push((asteroid.position.xy(), asteroid.radius));
+        commands.trigger(asteroid);
+    }
+}
+
+fn asteroid_positions(asteroid_query: &Query<(&Asteroid, &Transform)>) -> Vec<(Vec2, f32)> {
+    asteroid_query
+        .iter()
+        .map(|(asteroid, transform)| (transform.translation.xy(), asteroid.radius))
+        .collect()
+}
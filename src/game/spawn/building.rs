@@ -4,14 +4,18 @@ use avian3d::{
 };
 use bevy::prelude::*;
 use bevy_health_bar3d::configuration::{BarHeight, BarSettings, Percentage};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     game::{
-        assets::{HandleMap, ObjectKey, SfxKey},
+        assets::{DataAsset, HandleMap, ObjectKey, SfxKey},
         audio::sfx::PlaySfx,
         collision::CollisionLayer,
+        gameplay::{ResourceBundle, Resources},
         notifications::Notification,
-        turret::Turret,
+        power::{NeedsPower, PowerSource},
+        stats::RunStats,
+        turret::{TargetingPolicy, Turret},
         upgrades::{Upgrade, UpgradeType},
     },
     screen::Screen,
@@ -20,36 +24,214 @@ use crate::{
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_building);
+    app.observe(upgrade_building);
     app.register_type::<Destructable>();
     app.add_systems(Update, destroy_building.in_set(AppSet::PostUpdate));
 }
 
-#[derive(Copy, Clone, Debug, Reflect)]
-pub enum BuildingType {
-    Decoy,
-    Turret,
-    Sniper,
-    MiningUpgrade,
-    FireRateUpgrade,
+/// Identifies a building in the [`BuildingCatalogue`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BuildingType(pub String);
+
+/// Every building that can be placed during the build phase.
+/// Loaded from `assets/data/default.buildings.ron`.
+#[derive(Asset, Resource, TypePath, Clone, Debug, Deserialize)]
+pub struct BuildingCatalogue {
+    pub buildings: Vec<BuildingDefinition>,
+}
+impl BuildingCatalogue {
+    pub fn get(&self, building_type: &BuildingType) -> Option<&BuildingDefinition> {
+        self.buildings
+            .iter()
+            .find(|definition| &definition.id == building_type)
+    }
+}
+impl DataAsset for BuildingCatalogue {
+    const EXTENSIONS: &'static [&'static str] = &["buildings.ron"];
+    const PATH: &'static str = "data/default.buildings.ron";
 }
-impl BuildingType {
-    pub fn cost(&self) -> u32 {
-        match self {
-            BuildingType::Decoy => 3,
-            BuildingType::Turret => 12,
-            BuildingType::Sniper => 15,
-            BuildingType::FireRateUpgrade => 9,
-            BuildingType::MiningUpgrade => 14,
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BuildingDefinition {
+    pub id: BuildingType,
+    pub name: String,
+    pub description: String,
+    pub cost: ResourceBundle,
+    pub health: f32,
+    pub model: ObjectKey,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Radius of the ground the building takes up. Footprints can't overlap.
+    #[serde(default = "default_footprint")]
+    pub footprint: f32,
+    pub light: BuildingLight,
+    /// Buildings with a turret shoot at enemies within range.
+    #[serde(default)]
+    pub turret: Option<TurretDefinition>,
+    /// Buildings with an upgrade improve the ships for as long as they stand
+    /// and are powered.
+    #[serde(default)]
+    pub upgrade: Option<UpgradeType>,
+    /// Buildings with a power radius extend the power grid.
+    #[serde(default)]
+    pub power_radius: Option<f32>,
+    /// Upgrade levels that can be bought after placing the building, in order.
+    #[serde(default)]
+    pub tiers: Vec<BuildingTier>,
+}
+impl BuildingDefinition {
+    /// Turrets and upgrades only work while connected to the power grid.
+    pub fn needs_power(&self) -> bool {
+        self.turret.is_some() || self.upgrade.is_some()
+    }
+
+    pub fn max_level(&self) -> u32 {
+        self.tiers.len() as u32
+    }
+
+    /// The cost of upgrading from `level` to the next level, if there is one.
+    pub fn upgrade_cost(&self, level: u32) -> Option<ResourceBundle> {
+        self.tiers.get(level as usize).map(|tier| tier.cost)
+    }
+
+    /// Everything spent on a building that has been upgraded to `level`.
+    pub fn total_cost(&self, level: u32) -> ResourceBundle {
+        self.cost
+            + self
+                .tiers
+                .iter()
+                .take(level as usize)
+                .map(|tier| tier.cost)
+                .sum::<ResourceBundle>()
+    }
+
+    /// The stats of the building once upgraded to `level`.
+    pub fn stats(&self, level: u32) -> BuildingStats {
+        let mut stats = BuildingStats {
+            health: self.health,
+            model: self.model,
+            light_color: self.light.color,
+            turret: self.turret.clone(),
+            upgrade_strength: 1,
+            power_radius: self.power_radius,
+        };
+        for tier in self.tiers.iter().take(level as usize) {
+            stats.health = tier.health.unwrap_or(stats.health);
+            stats.model = tier.model.unwrap_or(stats.model);
+            stats.light_color = tier.light_color.unwrap_or(stats.light_color);
+            if let Some(turret) = &mut stats.turret {
+                turret.interval = tier.interval.unwrap_or(turret.interval);
+                turret.range = tier.range.unwrap_or(turret.range);
+                turret.damage = tier.damage.unwrap_or(turret.damage);
+            }
+            stats.upgrade_strength = tier.upgrade_strength.unwrap_or(stats.upgrade_strength);
+            if let Some(radius) = &mut stats.power_radius {
+                *radius = tier.power_radius.unwrap_or(*radius);
+            }
         }
+        stats
     }
 }
 
+/// An upgrade level of a building. Anything left unset keeps the value of the
+/// level below.
+#[derive(Clone, Debug, Deserialize)]
+pub struct BuildingTier {
+    pub cost: ResourceBundle,
+    #[serde(default)]
+    pub health: Option<f32>,
+    #[serde(default)]
+    pub model: Option<ObjectKey>,
+    #[serde(default)]
+    pub light_color: Option<[f32; 3]>,
+    #[serde(default)]
+    pub interval: Option<f32>,
+    #[serde(default)]
+    pub range: Option<f32>,
+    #[serde(default)]
+    pub damage: Option<f32>,
+    /// How many upgrades an upgrade building counts as.
+    #[serde(default)]
+    pub upgrade_strength: Option<u8>,
+    #[serde(default)]
+    pub power_radius: Option<f32>,
+}
+
+/// The stats of a building at a particular upgrade level.
+#[derive(Clone, Debug)]
+pub struct BuildingStats {
+    pub health: f32,
+    pub model: ObjectKey,
+    pub light_color: [f32; 3],
+    pub turret: Option<TurretDefinition>,
+    pub upgrade_strength: u8,
+    pub power_radius: Option<f32>,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+/// Footprint of buildings that don't set one.
+pub const DEFAULT_FOOTPRINT: f32 = 5.0;
+
+fn default_footprint() -> f32 {
+    DEFAULT_FOOTPRINT
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BuildingLight {
+    /// Linear RGB. Values above 1.0 make the light bloom.
+    pub color: [f32; 3],
+    pub radius: f32,
+    /// Height of the light above the building's origin.
+    pub height: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TurretDefinition {
+    /// Seconds between shots.
+    pub interval: f32,
+    /// Radius of the sensor that detects enemies.
+    pub range: f32,
+    /// Damage dealt by each bullet.
+    pub damage: f32,
+    /// Targeting policy of newly built turrets.
+    #[serde(default)]
+    pub targeting: TargetingPolicy,
+}
+
 #[derive(Event, Debug)]
 pub struct SpawnBuilding {
     pub building_type: BuildingType,
     pub position: Vec3,
+    /// Health to spawn with, e.g. when continuing a saved run. Defaults to
+    /// full health.
+    pub health: Option<f32>,
+    /// Upgrade level to spawn at.
+    pub level: u32,
+    /// Targeting policy of a turret, e.g. when continuing a saved run.
+    /// Defaults to the policy in its definition.
+    pub targeting: Option<TargetingPolicy>,
+}
+
+/// Buy the next upgrade level of a building.
+#[derive(Event, Debug)]
+pub struct UpgradeBuilding(pub Entity);
+
+/// A building placed by the player.
+#[derive(Component, Debug)]
+pub struct Building {
+    pub building_type: BuildingType,
+    pub level: u32,
 }
 
+/// The light on top of a building, which changes colour as it is upgraded and
+/// goes out while the building has no power.
+#[derive(Component, Debug)]
+pub struct StatusLight;
+
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
 pub struct Destructable {
@@ -64,6 +246,16 @@ impl Destructable {
             max_health: health,
         }
     }
+
+    pub fn max_health(&self) -> f32 {
+        self.max_health
+    }
+
+    /// Change the maximum health, keeping the amount of damage taken.
+    pub fn set_max_health(&mut self, max_health: f32) {
+        self.health += max_health - self.max_health;
+        self.max_health = max_health;
+    }
 }
 
 impl Percentage for Destructable {
@@ -76,192 +268,188 @@ fn spawn_building(
     trigger: Trigger<SpawnBuilding>,
     mut commands: Commands,
     object_handles: Res<HandleMap<ObjectKey>>,
+    catalogue: Res<BuildingCatalogue>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
     let event = trigger.event();
-    match event.building_type {
-        BuildingType::Decoy => {
-            commands
-                .spawn((
-                    Name::new("Decoy"),
-                    Destructable::new(200.0),
-                    SceneBundle {
-                        scene: object_handles[&ObjectKey::Decoy].clone_weak(),
-                        transform: Transform::from_translation(event.position)
-                            .with_scale(Vec3::splat(2.0)),
-                        ..Default::default()
-                    },
-                    StateScoped(Screen::Playing),
-                    BarSettings::<Destructable> {
-                        width: 5.0,
-                        offset: 3.0,
-                        height: BarHeight::Static(0.5),
-                        ..Default::default()
-                    },
-                ))
-                .with_children(|parent| {
-                    parent.spawn((
-                        Name::new("Light"),
-                        PbrBundle {
-                            mesh: meshes.add(Sphere { radius: 0.1 }.mesh().ico(1).unwrap()),
-                            material: materials.add(Color::srgb(0.5, 0.5, 6.0)),
-                            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.3)),
-                            ..Default::default()
-                        },
-                    ));
-                });
-        }
-        BuildingType::Turret => {
-            commands
-                .spawn((
-                    Name::new("Turret"),
-                    Turret::new(1.2),
-                    Destructable::new(50.0),
-                    SceneBundle {
-                        scene: object_handles[&ObjectKey::Decoy].clone_weak(),
-                        transform: Transform::from_translation(event.position)
-                            .with_scale(Vec3::splat(2.0)),
-                        ..Default::default()
-                    },
-                    StateScoped(Screen::Playing),
-                    BarSettings::<Destructable> {
-                        width: 5.0,
-                        offset: 3.0,
-                        height: BarHeight::Static(0.5),
-                        ..Default::default()
-                    },
-                    Collider::sphere(7.0),
-                    RigidBody::Static,
-                    Sensor,
-                    CollisionLayers::new(
-                        [CollisionLayer::Turret],
-                        LayerMask::from(CollisionLayer::Enemy),
-                    ),
-                ))
-                .with_children(|parent| {
-                    parent.spawn((
-                        Name::new("Light"),
-                        PbrBundle {
-                            mesh: meshes.add(Sphere { radius: 0.1 }.mesh().ico(1).unwrap()),
-                            material: materials.add(Color::srgb(6.0, 0.5, 0.5)),
-                            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.3)),
-                            ..Default::default()
-                        },
-                    ));
-                });
-        }
-        BuildingType::Sniper => {
-            commands
-                .spawn((
-                    Name::new("Sniper"),
-                    Turret::new(2.5),
-                    Destructable::new(50.0),
-                    SceneBundle {
-                        scene: object_handles[&ObjectKey::Decoy].clone_weak(),
-                        transform: Transform::from_translation(event.position)
-                            .with_scale(Vec3::splat(2.0)),
-                        ..Default::default()
-                    },
-                    StateScoped(Screen::Playing),
-                    BarSettings::<Destructable> {
-                        width: 5.0,
-                        offset: 3.0,
-                        height: BarHeight::Static(0.5),
-                        ..Default::default()
-                    },
-                    Collider::sphere(20.0),
-                    RigidBody::Static,
-                    Sensor,
-                    CollisionLayers::new(
-                        [CollisionLayer::Turret],
-                        LayerMask::from(CollisionLayer::Enemy),
-                    ),
-                ))
-                .with_children(|parent| {
-                    parent.spawn((
-                        Name::new("Light"),
-                        PbrBundle {
-                            mesh: meshes.add(Sphere { radius: 0.1 }.mesh().ico(1).unwrap()),
-                            material: materials.add(Color::srgb(3.0, 0.5, 3.0)),
-                            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.3)),
-                            ..Default::default()
-                        },
-                    ));
-                });
-        }
-        BuildingType::FireRateUpgrade => {
-            commands
-                .spawn((
-                    Name::new("Fire-rate upgrade"),
-                    Destructable::new(60.0),
-                    Upgrade(UpgradeType::FireRate),
-                    SceneBundle {
-                        scene: object_handles[&ObjectKey::Upgrade].clone_weak(),
-                        transform: Transform::from_translation(event.position),
-                        ..Default::default()
-                    },
-                    StateScoped(Screen::Playing),
-                    BarSettings::<Destructable> {
-                        width: 5.0,
-                        offset: 3.0,
-                        height: BarHeight::Static(0.5),
-                        ..Default::default()
-                    },
-                ))
-                .with_children(|parent| {
-                    parent.spawn((
-                        Name::new("Light"),
-                        PbrBundle {
-                            mesh: meshes.add(Sphere { radius: 0.2 }.mesh().ico(1).unwrap()),
-                            material: materials.add(Color::srgb(0.5, 6.0, 0.5)),
-                            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1.8)),
-                            ..Default::default()
-                        },
-                    ));
-                });
-        }
-        BuildingType::MiningUpgrade => {
-            commands
-                .spawn((
-                    Name::new("Mining upgrade"),
-                    Destructable::new(60.0),
-                    Upgrade(UpgradeType::MiningSpeed),
-                    SceneBundle {
-                        scene: object_handles[&ObjectKey::Upgrade].clone_weak(),
-                        transform: Transform::from_translation(event.position),
-                        ..Default::default()
-                    },
-                    StateScoped(Screen::Playing),
-                    BarSettings::<Destructable> {
-                        width: 5.0,
-                        offset: 3.0,
-                        height: BarHeight::Static(0.5),
-                        ..Default::default()
-                    },
-                ))
-                .with_children(|parent| {
-                    parent.spawn((
-                        Name::new("Light"),
-                        PbrBundle {
-                            mesh: meshes.add(Sphere { radius: 0.2 }.mesh().ico(1).unwrap()),
-                            material: materials.add(Color::srgb(3.0, 3.0, 0.5)),
-                            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1.8)),
-                            ..Default::default()
-                        },
-                    ));
-                });
+    let Some(definition) = catalogue.get(&event.building_type) else {
+        warn!("Unknown building type {:?}", event.building_type);
+        return;
+    };
+
+    let stats = definition.stats(event.level);
+    let mut destructable = Destructable::new(stats.health);
+    if let Some(health) = event.health {
+        destructable.health = health;
+    }
+    let mut entity = commands.spawn((
+        Name::new(definition.name.clone()),
+        Building {
+            building_type: event.building_type.clone(),
+            level: event.level,
+        },
+        destructable,
+        SceneBundle {
+            scene: object_handles[&stats.model].clone_weak(),
+            transform: Transform::from_translation(event.position)
+                .with_scale(Vec3::splat(definition.scale)),
+            ..Default::default()
+        },
+        StateScoped(Screen::Playing),
+        BarSettings::<Destructable> {
+            width: 5.0,
+            offset: 3.0,
+            height: BarHeight::Static(0.5),
+            ..Default::default()
+        },
+    ));
+    entity.with_children(|parent| {
+        let [r, g, b] = stats.light_color;
+        parent.spawn((
+            Name::new("Light"),
+            StatusLight,
+            PbrBundle {
+                mesh: meshes.add(
+                    Sphere {
+                        radius: definition.light.radius,
+                    }
+                    .mesh()
+                    .ico(1)
+                    .unwrap(),
+                ),
+                material: materials.add(Color::srgb(r, g, b)),
+                transform: Transform::from_translation(Vec3::new(
+                    0.0,
+                    0.0,
+                    definition.light.height,
+                )),
+                ..Default::default()
+            },
+        ));
+    });
+
+    if let Some(turret) = &stats.turret {
+        entity.insert((
+            Turret::new(
+                turret.interval,
+                turret.damage,
+                event.targeting.unwrap_or(turret.targeting),
+            ),
+            Collider::sphere(turret.range),
+            RigidBody::Static,
+            Sensor,
+            CollisionLayers::new(
+                [CollisionLayer::Turret],
+                LayerMask::from(CollisionLayer::Enemy),
+            ),
+        ));
+    }
+    if let Some(upgrade_type) = &definition.upgrade {
+        entity.insert(Upgrade {
+            upgrade_type: upgrade_type.clone(),
+            strength: stats.upgrade_strength,
+        });
+    }
+    if let Some(radius) = stats.power_radius {
+        entity.insert(PowerSource {
+            radius,
+            is_station: false,
+        });
+    }
+    if definition.needs_power() {
+        entity.insert(NeedsPower);
+    }
+}
+
+fn upgrade_building(
+    trigger: Trigger<UpgradeBuilding>,
+    mut commands: Commands,
+    object_handles: Res<HandleMap<ObjectKey>>,
+    catalogue: Res<BuildingCatalogue>,
+    mut resources: ResMut<Resources>,
+    mut notification_writer: EventWriter<Notification>,
+    mut building_query: Query<(
+        &mut Building,
+        &mut Destructable,
+        &mut Handle<Scene>,
+        Option<&mut Turret>,
+        Option<&mut Upgrade>,
+        Option<&mut PowerSource>,
+        &Children,
+        &Name,
+    )>,
+    light_query: Query<&Handle<StandardMaterial>, With<StatusLight>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let entity = trigger.event().0;
+    let Ok((
+        mut building,
+        mut destructable,
+        mut scene,
+        turret,
+        upgrade,
+        power_source,
+        children,
+        name,
+    )) = building_query.get_mut(entity)
+    else {
+        return;
+    };
+    let Some(definition) = catalogue.get(&building.building_type) else {
+        return;
+    };
+    let Some(cost) = definition.upgrade_cost(building.level) else {
+        notification_writer.send(Notification(format!("{} is fully upgraded", name.as_str())));
+        return;
+    };
+    if !resources.delivered.spend(&cost) {
+        notification_writer.send(Notification(format!("Not enough resources - needs {cost}")));
+        return;
+    }
+    building.level += 1;
+
+    let stats = definition.stats(building.level);
+    destructable.set_max_health(stats.health);
+    *scene = object_handles[&stats.model].clone_weak();
+    if let (Some(mut turret), Some(turret_definition)) = (turret, &stats.turret) {
+        turret.set_stats(turret_definition.interval, turret_definition.damage);
+        commands
+            .entity(entity)
+            .insert(Collider::sphere(turret_definition.range));
+    }
+    if let Some(mut upgrade) = upgrade {
+        upgrade.strength = stats.upgrade_strength;
+    }
+    if let (Some(mut power_source), Some(radius)) = (power_source, stats.power_radius) {
+        power_source.radius = radius;
+    }
+    for light in light_query.iter_many(children) {
+        if let Some(material) = materials.get_mut(light) {
+            let [r, g, b] = stats.light_color;
+            material.base_color = Color::srgb(r, g, b);
         }
     }
+
+    notification_writer.send(Notification(format!(
+        "{} upgraded to level {}",
+        name.as_str(),
+        building.level + 1
+    )));
+    commands.trigger(PlaySfx::Key(SfxKey::Build));
 }
 
 fn destroy_building(
     mut commands: Commands,
-    query: Query<(Entity, &Destructable, &Name), Changed<Destructable>>,
+    query: Query<(Entity, &Destructable, &Name, Has<Building>), Changed<Destructable>>,
     mut notification_writer: ResMut<Events<Notification>>,
+    mut stats: ResMut<RunStats>,
 ) {
-    for (entity, destructable, name) in query.iter() {
+    for (entity, destructable, name, is_building) in query.iter() {
         if destructable.health <= 0.0 {
+            if is_building {
+                stats.current().buildings_lost += 1;
+            }
             notification_writer.send(Notification(format!("{} destroyed", name.as_str())));
             commands.entity(entity).despawn_recursive();
             commands.trigger(PlaySfx::Key(SfxKey::Explode));
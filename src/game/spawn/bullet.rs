@@ -3,22 +3,47 @@ use avian3d::{
     dynamics::rigid_body::{LinearVelocity, RigidBody},
 };
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::game::{collision::CollisionLayer, combat::ShootEvent, util::DestroyAfterSecs};
+use crate::game::{
+    collision::CollisionLayer, combat::ShootEvent, spawn::building::BuildingType,
+    util::DestroyAfterSecs,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_bullet);
+    app.observe(spawn_hit_flash);
     app.init_resource::<BulletAssets>();
 }
 
+/// Speed of every bullet, in units per second.
+pub const BULLET_SPEED: f32 = 100.0;
+
 #[derive(Resource, Default)]
 pub struct BulletAssets {
     pub mesh: Option<Handle<Mesh>>,
     pub material: Option<Handle<StandardMaterial>>,
+    pub flash_material: Option<Handle<StandardMaterial>>,
 }
 
 #[derive(Component, Debug)]
-pub struct Bullet;
+pub struct Bullet {
+    pub damage: f32,
+    pub shooter: Shooter,
+}
+
+/// Who fired a bullet.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Shooter {
+    CombatShip,
+    Building(BuildingType),
+}
+
+/// Triggered when a bullet damages its target.
+#[derive(Event, Debug)]
+pub struct BulletHit {
+    pub target: Entity,
+}
 
 fn spawn_bullet(
     trigger: Trigger<ShootEvent>,
@@ -42,14 +67,17 @@ fn spawn_bullet(
 
     commands.spawn((
         Name::new("Bullet"),
-        Bullet,
+        Bullet {
+            damage: event.damage,
+            shooter: event.shooter.clone(),
+        },
         PbrBundle {
             mesh: bullet_assets.mesh.clone().unwrap(),
             material: bullet_assets.material.clone().unwrap(),
             transform: Transform::from_translation(event.position),
             ..Default::default()
         },
-        LinearVelocity(direction * 100.0),
+        LinearVelocity(direction * BULLET_SPEED),
         RigidBody::Dynamic,
         Collider::sphere(0.5),
         CollisionLayers::new(
@@ -59,3 +87,39 @@ fn spawn_bullet(
         DestroyAfterSecs::new(5.0),
     ));
 }
+
+const HIT_FLASH_DURATION: f32 = 0.1;
+
+fn spawn_hit_flash(
+    trigger: Trigger<BulletHit>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut bullet_assets: ResMut<BulletAssets>,
+) {
+    if bullet_assets.mesh.is_none() {
+        bullet_assets.mesh = Some(meshes.add(Sphere { radius: 0.5 }.mesh().ico(1).unwrap()));
+    }
+    if bullet_assets.flash_material.is_none() {
+        bullet_assets.flash_material = Some(materials.add(StandardMaterial {
+            base_color: Color::srgb(6.0, 6.0, 6.0),
+            unlit: true,
+            ..Default::default()
+        }));
+    }
+
+    commands
+        .entity(trigger.event().target)
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("HitFlash"),
+                PbrBundle {
+                    mesh: bullet_assets.mesh.clone().unwrap(),
+                    material: bullet_assets.flash_material.clone().unwrap(),
+                    transform: Transform::from_scale(Vec3::splat(2.5)),
+                    ..Default::default()
+                },
+                DestroyAfterSecs::new(HIT_FLASH_DURATION),
+            ));
+        });
+}
@@ -3,18 +3,25 @@ use avian3d::{
     dynamics::rigid_body::RigidBody,
 };
 use bevy::prelude::*;
+use bevy_health_bar3d::configuration::{ColorScheme, ForegroundColor, Percentage};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     game::{
-        assets::{HandleMap, ObjectKey},
+        assets::{HandleMap, ObjectKey, SfxKey},
+        audio::sfx::PlaySfx,
         collision::CollisionLayer,
+        movement::Velocity,
+        rng::GameRng,
+        stats::RunStats,
+        turret::Turret,
     },
     screen::Screen,
     AppSet,
 };
 
-use super::building::Destructable;
+use super::{building::Destructable, bullet::Shooter, station::Station};
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_enemy);
@@ -25,12 +32,129 @@ pub(super) fn plugin(app: &mut App) {
             .chain()
             .in_set(AppSet::Update),
     );
+    app.add_systems(Update, destroy_enemy.in_set(AppSet::PostUpdate));
+    app.insert_resource(
+        ColorScheme::<Enemy>::new().foreground_color(ForegroundColor::Static(
+            bevy::color::palettes::basic::RED.into(),
+        )),
+    );
 }
 
 #[derive(Event, Debug)]
 pub struct SpawnEnemy {
     pub distance: f32,
     pub damage_mult: f32,
+    /// Direction from the station to spawn at, in radians. Random if not set.
+    pub bearing: Option<f32>,
+    pub kind: EnemyKind,
+    /// Where to spawn, e.g. when continuing a saved run. Overrides the
+    /// distance and bearing.
+    pub position: Option<Vec3>,
+    /// Health to spawn with. Defaults to full health.
+    pub health: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum EnemyKind {
+    #[default]
+    Standard,
+    /// Fast and fragile.
+    Scout,
+    /// Slow, armoured and hits hard.
+    Brute,
+    /// Attacks from outside the range of basic turrets, preferring to take
+    /// them out first.
+    Gunship,
+    /// Dives straight at the station and explodes on arrival.
+    Bomber,
+}
+
+/// Which buildings an enemy kind likes to attack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetPreference {
+    Nearest,
+    Turrets,
+    Station,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EnemyStats {
+    pub speed: f32,
+    pub health: f32,
+    /// Damage per second while attacking, or the damage of the explosion for
+    /// enemies that explode.
+    pub damage: f32,
+    pub attack_range: f32,
+    /// Scale of the model. The collider scales with it.
+    pub size: f32,
+    pub target: TargetPreference,
+    /// Chance of wandering around instead of picking a target.
+    pub wander_probability: f32,
+    pub explodes: bool,
+}
+
+impl EnemyKind {
+    pub fn stats(self) -> EnemyStats {
+        match self {
+            EnemyKind::Standard => EnemyStats {
+                speed: 10.0,
+                health: 50.0,
+                damage: 5.0,
+                attack_range: 10.0,
+                size: 1.0,
+                target: TargetPreference::Nearest,
+                wander_probability: 0.6,
+                explodes: false,
+            },
+            EnemyKind::Scout => EnemyStats {
+                speed: 20.0,
+                health: 25.0,
+                damage: 3.0,
+                attack_range: 8.0,
+                size: 0.7,
+                target: TargetPreference::Nearest,
+                wander_probability: 0.3,
+                explodes: false,
+            },
+            EnemyKind::Brute => EnemyStats {
+                speed: 6.0,
+                health: 150.0,
+                damage: 12.0,
+                attack_range: 9.0,
+                size: 1.6,
+                target: TargetPreference::Nearest,
+                wander_probability: 0.2,
+                explodes: false,
+            },
+            EnemyKind::Gunship => EnemyStats {
+                speed: 9.0,
+                health: 60.0,
+                damage: 6.0,
+                attack_range: 15.0,
+                size: 1.2,
+                target: TargetPreference::Turrets,
+                wander_probability: 0.2,
+                explodes: false,
+            },
+            EnemyKind::Bomber => EnemyStats {
+                speed: 16.0,
+                health: 30.0,
+                damage: 120.0,
+                attack_range: 4.0,
+                size: 0.9,
+                target: TargetPreference::Station,
+                wander_probability: 0.0,
+                explodes: true,
+            },
+        }
+    }
+}
+
+/// An enemy was shot down, as opposed to exploding on its own.
+#[derive(Event, Debug)]
+pub struct EnemyKilled {
+    pub kind: EnemyKind,
+    pub shooter: Shooter,
 }
 
 #[derive(Default, Debug, Reflect)]
@@ -46,33 +170,55 @@ pub enum EnemyState {
 #[reflect(Component)]
 pub struct Enemy {
     pub health: f32,
+    max_health: f32,
     pub state: EnemyState,
     pub damage: f32,
+    pub kind: EnemyKind,
 }
 impl Enemy {
-    pub fn new(damage: f32) -> Self {
+    /// An enemy of the given kind, with its health and damage scaled by
+    /// `damage_mult`.
+    pub fn new(kind: EnemyKind, damage_mult: f32) -> Self {
+        let stats = kind.stats();
         Self {
-            health: 100.0,
-            damage,
+            health: stats.health * damage_mult,
+            max_health: stats.health * damage_mult,
+            damage: stats.damage * damage_mult,
             state: EnemyState::None,
+            kind,
         }
     }
+
+    /// The multiplier the enemy was spawned with.
+    pub fn damage_mult(&self) -> f32 {
+        self.max_health / self.kind.stats().health
+    }
+}
+
+impl Percentage for Enemy {
+    fn value(&self) -> f32 {
+        self.health / self.max_health
+    }
 }
 
 fn spawn_enemy(
     trigger: Trigger<SpawnEnemy>,
     mut commands: Commands,
     object_handles: Res<HandleMap<ObjectKey>>,
+    mut rng: ResMut<GameRng>,
 ) {
     let event = trigger.event();
 
-    let mut rng = rand::thread_rng();
-    let random_angle = rng.gen::<f32>() * std::f32::consts::PI * 2.0;
-    let position = Vec3::new(
-        random_angle.cos() * event.distance,
-        random_angle.sin() * event.distance,
-        -3.0,
-    );
+    let position = event.position.unwrap_or_else(|| {
+        let angle = event
+            .bearing
+            .unwrap_or_else(|| rng.gen::<f32>() * std::f32::consts::PI * 2.0);
+        Vec3::new(
+            angle.cos() * event.distance,
+            angle.sin() * event.distance,
+            -3.0,
+        )
+    });
     let mut random_rotation = Quat::IDENTITY;
     random_rotation *= Quat::from_rotation_x(f32::to_radians(rng.gen_range(0.0..360.0)));
     random_rotation *= Quat::from_rotation_y(f32::to_radians(rng.gen_range(0.0..360.0)));
@@ -81,11 +227,16 @@ fn spawn_enemy(
     let transform = Transform {
         translation: position,
         rotation: random_rotation,
-        scale: Vec3::splat(event.damage_mult),
+        scale: Vec3::splat(event.kind.stats().size),
     };
+    let mut enemy = Enemy::new(event.kind, event.damage_mult);
+    if let Some(health) = event.health {
+        enemy.health = health;
+    }
     commands.spawn((
-        Name::new("Enemy"),
-        Enemy::new(5.0 * event.damage_mult),
+        Name::new(format!("{:?}", event.kind)),
+        enemy,
+        Velocity::default(),
         SceneBundle {
             scene: object_handles[&ObjectKey::Enemy].clone_weak(),
             transform,
@@ -102,35 +253,49 @@ fn spawn_enemy(
     ));
 }
 
-const WANDER_PROBABILITY: f32 = 0.6;
-
 fn choose_target(
     mut enemy_query: Query<(&Transform, &mut Enemy), Without<Destructable>>,
-    building_query: Query<(Entity, &Transform), With<Destructable>>,
+    building_query: Query<(Entity, &Transform, Has<Station>, Has<Turret>), With<Destructable>>,
+    mut rng: ResMut<GameRng>,
 ) {
     for (enemy_transform, mut enemy) in enemy_query.iter_mut() {
         if let EnemyState::None = enemy.state {
-            if rand::random::<f32>() < WANDER_PROBABILITY {
-                let angle = rand::random::<f32>() * std::f32::consts::PI * 2.0;
+            let stats = enemy.kind.stats();
+            if rng.gen::<f32>() < stats.wander_probability {
+                let angle = rng.gen::<f32>() * std::f32::consts::PI * 2.0;
                 let direction = Vec2::new(angle.cos(), angle.sin());
-                let distance = rand::random::<f32>() * 20.0;
+                let distance = rng.gen::<f32>() * 20.0;
                 let target =
                     Vec2::new(enemy_transform.translation.x, enemy_transform.translation.y)
                         + direction * distance;
                 enemy.state = EnemyState::Wander(target);
             } else {
-                let mut closest_building = None;
-                let mut closest_distance = f32::MAX;
-                for (entity, building_transform) in building_query.iter() {
-                    let distance = enemy_transform
-                        .translation
-                        .distance(building_transform.translation);
-                    if distance < closest_distance {
-                        closest_distance = distance;
-                        closest_building = Some(entity);
+                let is_preferred = |is_station: bool, is_turret: bool| match stats.target {
+                    TargetPreference::Nearest => true,
+                    TargetPreference::Turrets => is_turret,
+                    TargetPreference::Station => is_station,
+                };
+                let closest = |preferred_only: bool| {
+                    let mut closest_building = None;
+                    let mut closest_distance = f32::MAX;
+                    for (entity, building_transform, is_station, is_turret) in building_query.iter()
+                    {
+                        if preferred_only && !is_preferred(is_station, is_turret) {
+                            continue;
+                        }
+                        let distance = enemy_transform
+                            .translation
+                            .distance(building_transform.translation);
+                        if distance < closest_distance {
+                            closest_distance = distance;
+                            closest_building = Some(entity);
+                        }
                     }
-                }
-                if let Some(entity) = closest_building {
+                    closest_building
+                };
+                // Fall back to the nearest building if there are none of the
+                // preferred kind
+                if let Some(entity) = closest(true).or_else(|| closest(false)) {
                     enemy.state = EnemyState::TravelingTo(entity);
                 }
             }
@@ -138,26 +303,26 @@ fn choose_target(
     }
 }
 
-const ATTACK_DISTANCE: f32 = 10.0;
+/// Enemies wander slower than they travel to a target.
+const WANDER_SPEED_FACTOR: f32 = 0.7;
 
 fn travel_to_target(
-    mut enemy_query: Query<(&mut Transform, &mut Enemy), Without<Destructable>>,
+    mut enemy_query: Query<(&Transform, &mut Velocity, &mut Enemy), Without<Destructable>>,
     building_query: Query<&Transform, With<Destructable>>,
-    time: Res<Time>,
 ) {
-    for (mut enemy_transform, mut enemy) in enemy_query.iter_mut() {
+    for (enemy_transform, mut velocity, mut enemy) in enemy_query.iter_mut() {
+        let stats = enemy.kind.stats();
+        velocity.0 = Vec2::ZERO;
         match enemy.state {
             EnemyState::TravelingTo(target) => {
                 if let Ok(target_transform) = building_query.get(target) {
                     let direction =
                         target_transform.translation.xy() - enemy_transform.translation.xy();
                     let distance = direction.length();
-                    let velocity = 10.0;
-                    let movement = direction.normalize() * velocity * time.delta_seconds();
-                    if distance < ATTACK_DISTANCE {
+                    if distance < stats.attack_range {
                         enemy.state = EnemyState::Attacking(target);
                     } else {
-                        enemy_transform.translation += movement.extend(0.0);
+                        velocity.0 = direction.normalize() * stats.speed;
                     }
                 } else {
                     enemy.state = EnemyState::None;
@@ -167,12 +332,10 @@ fn travel_to_target(
                 let direction = target
                     - Vec2::new(enemy_transform.translation.x, enemy_transform.translation.y);
                 let distance = direction.length();
-                let velocity = 7.0;
-                let movement = direction.normalize() * velocity * time.delta_seconds();
                 if distance < 0.1 {
                     enemy.state = EnemyState::None;
                 } else {
-                    enemy_transform.translation += movement.extend(0.0);
+                    velocity.0 = direction.normalize() * stats.speed * WANDER_SPEED_FACTOR;
                 }
             }
             _ => {}
@@ -182,16 +345,35 @@ fn travel_to_target(
 
 fn attack_target(
     mut enemy_query: Query<&mut Enemy, Without<Destructable>>,
-    mut building_query: Query<&mut Destructable, With<Destructable>>,
+    mut building_query: Query<(&mut Destructable, Has<Station>)>,
+    mut stats: ResMut<RunStats>,
     time: Res<Time>,
 ) {
     for mut enemy in enemy_query.iter_mut() {
         if let EnemyState::Attacking(target) = enemy.state {
-            if let Ok(mut target) = building_query.get_mut(target) {
-                target.health -= enemy.damage * time.delta_seconds();
+            if let Ok((mut target, is_station)) = building_query.get_mut(target) {
+                let damage = if enemy.kind.stats().explodes {
+                    enemy.health = 0.0;
+                    enemy.damage
+                } else {
+                    enemy.damage * time.delta_seconds()
+                };
+                target.health -= damage;
+                if is_station {
+                    stats.current().station_damage += damage;
+                }
             } else {
                 enemy.state = EnemyState::None;
             }
         }
     }
 }
+
+fn destroy_enemy(mut commands: Commands, query: Query<(Entity, &Enemy), Changed<Enemy>>) {
+    for (entity, enemy) in query.iter() {
+        if enemy.health <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            commands.trigger(PlaySfx::Key(SfxKey::Explode));
+        }
+    }
+}
@@ -2,8 +2,13 @@
 
 use bevy::prelude::*;
 
+use crate::game::save::Resume;
+
 use super::{
-    asteroid::{SpawnAsteroid, SpawnRandomAsteroid},
+    asteroid::SpawnAsteroid,
+    asteroid_field::SpawnAsteroidField,
+    building::SpawnBuilding,
+    enemy::SpawnEnemy,
     player::{SpawnCombatShip, SpawnMiningShip},
     station::SpawnStation,
 };
@@ -15,24 +20,49 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Event, Debug)]
 pub struct SpawnLevel;
 
-fn spawn_level(_trigger: Trigger<SpawnLevel>, mut commands: Commands) {
+fn spawn_level(_trigger: Trigger<SpawnLevel>, mut commands: Commands, resume: Option<Res<Resume>>) {
     commands.trigger(SpawnStation);
 
     commands.trigger(SpawnCombatShip);
     commands.trigger(SpawnMiningShip);
 
-    commands.trigger(SpawnAsteroid {
-        position: Vec3::new(-40.0, -15.0, -5.0),
-        is_visible: true,
-    });
-    commands.trigger(SpawnAsteroid {
-        position: Vec3::new(60.0, -50.0, -5.0),
-        is_visible: true,
+    // Resumed runs get the same belts back from their seed, but keep the
+    // asteroids they had.
+    commands.trigger(SpawnAsteroidField {
+        populate: resume.is_none(),
     });
-    commands.trigger(SpawnRandomAsteroid);
-    commands.trigger(SpawnRandomAsteroid);
-    commands.trigger(SpawnRandomAsteroid);
-    commands.trigger(SpawnRandomAsteroid);
+
+    if let Some(Resume(saved_run)) = resume.as_deref() {
+        for asteroid in &saved_run.asteroids {
+            commands.trigger(SpawnAsteroid {
+                position: asteroid.position,
+                kind: asteroid.kind,
+                radius: asteroid.radius,
+                max_resources: asteroid.max_resources,
+                is_visible: asteroid.is_visible,
+                contained_resources: Some(asteroid.contained_resources),
+            });
+        }
+        for building in &saved_run.buildings {
+            commands.trigger(SpawnBuilding {
+                building_type: building.building_type.clone(),
+                position: building.position,
+                health: Some(building.health),
+                level: building.level,
+                targeting: building.targeting,
+            });
+        }
+        for enemy in &saved_run.enemies {
+            commands.trigger(SpawnEnemy {
+                distance: 0.0,
+                damage_mult: enemy.damage_mult,
+                bearing: None,
+                kind: enemy.kind,
+                position: Some(enemy.position),
+                health: Some(enemy.health),
+            });
+        }
+    }
 
     commands.spawn((
         Name::new("Directional light"),
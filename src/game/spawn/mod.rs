@@ -5,6 +5,7 @@
 use bevy::prelude::*;
 
 pub mod asteroid;
+pub mod asteroid_field;
 pub mod building;
 pub mod bullet;
 pub mod enemy;
@@ -17,6 +18,7 @@ pub(super) fn plugin(app: &mut App) {
         level::plugin,
         player::plugin,
         asteroid::plugin,
+        asteroid_field::plugin,
         building::plugin,
         enemy::plugin,
         bullet::plugin,
@@ -1,9 +1,13 @@
 use bevy::{ecs::component::StorageType, prelude::*};
 use bevy_health_bar3d::configuration::{BarHeight, BarSettings};
+use rand::Rng;
 
 use crate::{
     game::{
         assets::{HandleMap, ObjectKey},
+        gameplay::GameplayManager,
+        power::PowerSource,
+        rng::GameRng,
         util::Spin,
         waypoint::Waypointed,
     },
@@ -16,6 +20,12 @@ pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_station);
 }
 
+/// Radius of the station's ring, as seen from above.
+pub const STATION_RADIUS: f32 = 27.0;
+
+/// How far the station's own power reaches. Pylons extend it further.
+pub const STATION_POWER_RADIUS: f32 = 45.0;
+
 #[derive(Event, Debug)]
 pub struct SpawnStation;
 
@@ -27,8 +37,12 @@ impl Component for Station {
     const STORAGE_TYPE: StorageType = StorageType::Table;
 
     fn register_component_hooks(hooks: &mut bevy::ecs::component::ComponentHooks) {
-        // Trigger game over when this component is removed
+        // Trigger game over when this component is removed, unless the station
+        // is only being despawned because the player left the game
         hooks.on_remove(|mut world, _entity, _component_id| {
+            if *world.resource::<State<Screen>>().get() != Screen::Playing {
+                return;
+            }
             let mut next = world.get_resource_mut::<NextState<Screen>>().unwrap();
             next.set(Screen::GameOver);
         });
@@ -44,6 +58,8 @@ fn spawn_station(
     object_handles: Res<HandleMap<ObjectKey>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rng: ResMut<GameRng>,
+    manager: Res<GameplayManager>,
 ) {
     let mut transform = Transform::from_xyz(0.0, 0.0, -30.0);
     transform.rotate_z(f32::to_radians(90.0));
@@ -62,7 +78,11 @@ fn spawn_station(
                 rotation_axis: transform.rotation * Vec3::Y,
                 rotation_speed: 0.05,
             },
-            Destructable::new(2000.0),
+            Destructable::new(manager.difficulty.station_health),
+            PowerSource {
+                radius: STATION_POWER_RADIUS,
+                is_station: true,
+            },
             StateScoped(Screen::Playing),
             BarSettings::<Destructable> {
                 width: 10.0,
@@ -80,8 +100,8 @@ fn spawn_station(
                 ..Default::default()
             });
             for _ in 0..150 {
-                let angle = rand::random::<f32>() * std::f32::consts::PI * 2.0;
-                let z = rand::random::<f32>() - 0.5;
+                let angle = rng.gen::<f32>() * std::f32::consts::PI * 2.0;
+                let z = rng.gen::<f32>() - 0.5;
                 let position = Vec3::new(angle.sin() * 9.5, z, angle.cos() * 9.5);
                 parent.spawn((
                     Name::new("Light"),
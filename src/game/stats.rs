@@ -0,0 +1,119 @@
+//! Statistics of the current run, broken down by cycle.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    gameplay::GameplayManager,
+    spawn::{
+        building::Building,
+        bullet::Shooter,
+        enemy::{EnemyKilled, SpawnEnemy},
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RunStats>();
+    app.observe(count_spawned_enemy);
+    app.observe(count_kill);
+    app.add_systems(OnEnter(Screen::Playing), reset_stats);
+    app.add_systems(
+        Update,
+        start_cycles
+            .run_if(in_state(Screen::Playing))
+            .in_set(AppSet::TickTimers),
+    );
+    app.add_systems(
+        Update,
+        count_buildings
+            .run_if(in_state(Screen::Playing))
+            .in_set(AppSet::PostUpdate),
+    );
+}
+
+/// What happened during the current run.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunStats {
+    /// One entry for every cycle that has started, in order.
+    pub cycles: Vec<CycleStats>,
+    /// Buildings currently standing.
+    pub buildings_standing: u32,
+}
+impl RunStats {
+    /// The stats of the cycle in progress.
+    pub fn current(&mut self) -> &mut CycleStats {
+        if self.cycles.is_empty() {
+            self.cycles.push(CycleStats::default());
+        }
+        self.cycles.last_mut().unwrap()
+    }
+
+    /// The stats of every cycle added together.
+    pub fn total(&self) -> CycleStats {
+        let mut total = CycleStats::default();
+        for cycle in &self.cycles {
+            total.enemies_spawned += cycle.enemies_spawned;
+            for (shooter, kills) in &cycle.kills {
+                *total.kills.entry(shooter.clone()).or_default() += kills;
+            }
+            total.resources_mined += cycle.resources_mined;
+            total.resources_delivered += cycle.resources_delivered;
+            total.buildings_placed += cycle.buildings_placed;
+            total.buildings_lost += cycle.buildings_lost;
+            total.station_damage += cycle.station_damage;
+            total.asteroids_depleted += cycle.asteroids_depleted;
+        }
+        total
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CycleStats {
+    pub enemies_spawned: u32,
+    /// Enemies shot down, by whoever fired the final shot.
+    pub kills: BTreeMap<Shooter, u32>,
+    pub resources_mined: u32,
+    pub resources_delivered: u32,
+    pub buildings_placed: u32,
+    /// Buildings destroyed by enemies. Sold buildings aren't counted.
+    pub buildings_lost: u32,
+    pub station_damage: f32,
+    pub asteroids_depleted: u32,
+}
+impl CycleStats {
+    pub fn total_kills(&self) -> u32 {
+        self.kills.values().sum()
+    }
+}
+
+fn reset_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+/// Add an entry for each cycle as it starts.
+fn start_cycles(mut stats: ResMut<RunStats>, manager: Res<GameplayManager>) {
+    let cycles = manager.cycle as usize + 1;
+    if stats.cycles.len() < cycles {
+        stats.cycles.resize_with(cycles, Default::default);
+    }
+}
+
+fn count_spawned_enemy(_trigger: Trigger<SpawnEnemy>, mut stats: ResMut<RunStats>) {
+    stats.current().enemies_spawned += 1;
+}
+
+fn count_kill(trigger: Trigger<EnemyKilled>, mut stats: ResMut<RunStats>) {
+    let shooter = trigger.event().shooter.clone();
+    *stats.current().kills.entry(shooter).or_default() += 1;
+}
+
+fn count_buildings(mut stats: ResMut<RunStats>, building_query: Query<(), With<Building>>) {
+    let buildings = building_query.iter().count() as u32;
+    if stats.buildings_standing != buildings {
+        stats.buildings_standing = buildings;
+    }
+}
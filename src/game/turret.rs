@@ -1,9 +1,22 @@
+use std::time::Duration;
+
 use avian3d::collision::CollidingEntities;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{screen::Screen, AppSet};
 
-use super::{combat::ShootEvent, spawn::enemy::Enemy};
+use super::{
+    combat::ShootEvent,
+    movement::Velocity,
+    power::Powered,
+    spawn::{
+        building::Building,
+        bullet::{Shooter, BULLET_SPEED},
+        enemy::Enemy,
+        station::Station,
+    },
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(Update, tick_timer.in_set(AppSet::TickTimers));
@@ -18,11 +31,60 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Component, Debug)]
 pub struct Turret {
     shoot_timer: Timer,
+    damage: f32,
+    pub targeting: TargetingPolicy,
+    target: Option<Entity>,
 }
 impl Turret {
-    pub fn new(shoot_interval: f32) -> Self {
+    pub fn new(shoot_interval: f32, damage: f32, targeting: TargetingPolicy) -> Self {
         Self {
             shoot_timer: Timer::from_seconds(shoot_interval, TimerMode::Repeating),
+            damage,
+            targeting,
+            target: None,
+        }
+    }
+
+    pub fn set_stats(&mut self, shoot_interval: f32, damage: f32) {
+        self.shoot_timer
+            .set_duration(Duration::from_secs_f32(shoot_interval));
+        self.damage = damage;
+    }
+}
+
+/// How a turret picks which enemy in range to shoot at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum TargetingPolicy {
+    #[default]
+    Nearest,
+    LowestHealth,
+    /// The enemy that deals the most damage.
+    HighestThreat,
+    ClosestToStation,
+    /// Keep shooting the same enemy until it dies or leaves range.
+    Sticky,
+}
+impl TargetingPolicy {
+    pub const ALL: [TargetingPolicy; 5] = [
+        TargetingPolicy::Nearest,
+        TargetingPolicy::LowestHealth,
+        TargetingPolicy::HighestThreat,
+        TargetingPolicy::ClosestToStation,
+        TargetingPolicy::Sticky,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|policy| *policy == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TargetingPolicy::Nearest => "Nearest",
+            TargetingPolicy::LowestHealth => "Lowest health",
+            TargetingPolicy::HighestThreat => "Highest threat",
+            TargetingPolicy::ClosestToStation => "Closest to station",
+            TargetingPolicy::Sticky => "Stick to target",
         }
     }
 }
@@ -33,22 +95,233 @@ fn tick_timer(mut query: Query<&mut Turret>, time: Res<Time>) {
     }
 }
 
+/// An enemy within range of a turret.
+struct Candidate<'a> {
+    entity: Entity,
+    position: Vec3,
+    velocity: Vec2,
+    enemy: &'a Enemy,
+}
+
 fn shoot(
     mut commands: Commands,
-    turret_query: Query<(&Turret, &Transform, &CollidingEntities)>,
-    enemy_query: Query<&Transform, (With<Enemy>, Without<Turret>)>,
+    mut turret_query: Query<
+        (&mut Turret, &Building, &Transform, &CollidingEntities),
+        With<Powered>,
+    >,
+    enemy_query: Query<(&Transform, &Velocity, &Enemy), Without<Turret>>,
+    station_query: Query<&Transform, (With<Station>, Without<Turret>, Without<Enemy>)>,
 ) {
-    for (turret, turret_transform, colliding_entities) in turret_query.iter() {
-        if turret.shoot_timer.just_finished() {
-            if let Some(enemy_entity) = colliding_entities.iter().next() {
-                if let Ok(enemy_transform) = enemy_query.get(*enemy_entity) {
-                    let direction = enemy_transform.translation - turret_transform.translation;
-                    commands.trigger(ShootEvent {
-                        position: turret_transform.translation,
-                        direction,
+    let station_position = station_query
+        .get_single()
+        .map(|transform| transform.translation.xy())
+        .unwrap_or_default();
+    for (mut turret, building, turret_transform, colliding_entities) in turret_query.iter_mut() {
+        if !turret.shoot_timer.just_finished() {
+            continue;
+        }
+        let candidates: Vec<_> = colliding_entities
+            .iter()
+            .filter_map(|entity| {
+                enemy_query
+                    .get(*entity)
+                    .ok()
+                    .map(|(transform, velocity, enemy)| Candidate {
+                        entity: *entity,
+                        position: transform.translation,
+                        velocity: velocity.0,
+                        enemy,
                     })
-                }
-            }
+            })
+            .collect();
+        let turret_position = turret_transform.translation;
+        let target = select_target(
+            turret.targeting,
+            &candidates,
+            turret_position.xy(),
+            station_position,
+            turret.target,
+        );
+
+        turret.target = target.map(|candidate| candidate.entity);
+        if let Some(target) = target {
+            let direction = lead_target(
+                turret_position,
+                target.position,
+                target.velocity.extend(0.0),
+                BULLET_SPEED,
+            );
+            commands.trigger(ShootEvent {
+                position: turret_position,
+                direction,
+                damage: turret.damage,
+                shooter: Shooter::Building(building.building_type.clone()),
+            })
         }
     }
 }
+
+/// The candidate that `policy` picks, given the turret's previous target.
+fn select_target<'a>(
+    policy: TargetingPolicy,
+    candidates: &'a [Candidate<'a>],
+    turret_position: Vec2,
+    station_position: Vec2,
+    previous_target: Option<Entity>,
+) -> Option<&'a Candidate<'a>> {
+    match policy {
+        TargetingPolicy::Nearest => closest_to(candidates, turret_position),
+        TargetingPolicy::LowestHealth => candidates
+            .iter()
+            .min_by(|a, b| a.enemy.health.total_cmp(&b.enemy.health)),
+        TargetingPolicy::HighestThreat => candidates
+            .iter()
+            .max_by(|a, b| a.enemy.damage.total_cmp(&b.enemy.damage)),
+        TargetingPolicy::ClosestToStation => closest_to(candidates, station_position),
+        TargetingPolicy::Sticky => candidates
+            .iter()
+            .find(|candidate| Some(candidate.entity) == previous_target)
+            .or_else(|| closest_to(candidates, turret_position)),
+    }
+}
+
+fn closest_to<'a>(candidates: &'a [Candidate<'a>], point: Vec2) -> Option<&'a Candidate<'a>> {
+    candidates.iter().min_by(|a, b| {
+        a.position
+            .xy()
+            .distance_squared(point)
+            .total_cmp(&b.position.xy().distance_squared(point))
+    })
+}
+
+/// The direction to fire a projectile at `speed` from `origin` so that it hits
+/// a target moving at a constant velocity. Falls back to aiming at the target's
+/// current position if the projectile is too slow to ever catch it.
+fn lead_target(origin: Vec3, target: Vec3, target_velocity: Vec3, speed: f32) -> Vec3 {
+    let offset = target - origin;
+    // Solve |offset + target_velocity * t| = speed * t for the time of impact t
+    let a = target_velocity.length_squared() - speed * speed;
+    let b = 2.0 * offset.dot(target_velocity);
+    let c = offset.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        // The target moves as fast as the projectile, so the equation is linear
+        (b < 0.0).then(|| -c / b)
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            None
+        } else {
+            let root = discriminant.sqrt();
+            [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+                .into_iter()
+                .filter(|time| *time > 0.0)
+                .min_by(|a, b| a.total_cmp(b))
+        }
+    };
+
+    match time {
+        Some(time) => offset + target_velocity * time,
+        None => offset,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::spawn::enemy::EnemyKind;
+
+    const TURRET: Vec2 = Vec2::ZERO;
+    const STATION: Vec2 = Vec2::new(-30.0, 0.0);
+
+    /// Picks between a weak scout right next to the turret and a brute further
+    /// away, on the station's side.
+    fn select(policy: TargetingPolicy, previous_target: Option<Entity>) -> Option<Entity> {
+        let scout = Enemy::new(EnemyKind::Scout, 1.0);
+        let brute = Enemy::new(EnemyKind::Brute, 1.0);
+        let candidates = [
+            Candidate {
+                entity: Entity::from_raw(1),
+                position: Vec3::new(5.0, 0.0, 0.0),
+                velocity: Vec2::ZERO,
+                enemy: &scout,
+            },
+            Candidate {
+                entity: Entity::from_raw(2),
+                position: Vec3::new(-15.0, 0.0, 0.0),
+                velocity: Vec2::ZERO,
+                enemy: &brute,
+            },
+        ];
+        select_target(policy, &candidates, TURRET, STATION, previous_target)
+            .map(|candidate| candidate.entity)
+    }
+
+    #[test]
+    fn nearest_picks_the_closest_enemy() {
+        assert_eq!(
+            select(TargetingPolicy::Nearest, None),
+            Some(Entity::from_raw(1))
+        );
+    }
+
+    #[test]
+    fn lowest_health_picks_the_weakest_enemy() {
+        assert_eq!(
+            select(TargetingPolicy::LowestHealth, None),
+            Some(Entity::from_raw(1))
+        );
+    }
+
+    #[test]
+    fn highest_threat_picks_the_most_damaging_enemy() {
+        assert_eq!(
+            select(TargetingPolicy::HighestThreat, None),
+            Some(Entity::from_raw(2))
+        );
+    }
+
+    #[test]
+    fn closest_to_station_protects_the_station() {
+        assert_eq!(
+            select(TargetingPolicy::ClosestToStation, None),
+            Some(Entity::from_raw(2))
+        );
+    }
+
+    #[test]
+    fn sticky_keeps_its_target_when_a_nearer_enemy_arrives() {
+        assert_eq!(
+            select(TargetingPolicy::Sticky, None),
+            Some(Entity::from_raw(1))
+        );
+        assert_eq!(
+            select(TargetingPolicy::Sticky, Some(Entity::from_raw(2))),
+            Some(Entity::from_raw(2))
+        );
+        // A target that left range is replaced by the nearest enemy
+        assert_eq!(
+            select(TargetingPolicy::Sticky, Some(Entity::from_raw(3))),
+            Some(Entity::from_raw(1))
+        );
+    }
+
+    #[test]
+    fn lead_target_hits_moving_targets() {
+        let origin = Vec3::new(0.0, 0.0, 0.0);
+        let target = Vec3::new(30.0, 0.0, -3.0);
+        let target_velocity = Vec3::new(0.0, 15.0, 0.0);
+        let speed = 100.0;
+
+        let direction = lead_target(origin, target, target_velocity, speed);
+        // Find when the bullet reaches the target's path and check that the target
+        // is there at the same time
+        let time = direction.length() / speed;
+        let bullet_position = origin + direction.normalize() * speed * time;
+        let target_position = target + target_velocity * time;
+        assert!(bullet_position.distance(target_position) < 1e-3);
+
+        // A stationary target is aimed at directly
+        assert_eq!(lead_target(origin, target, Vec3::ZERO, speed), target);
+    }
+}
@@ -2,22 +2,32 @@ use bevy::prelude::*;
 
 use crate::{screen::Screen, ui::prelude::*};
 
-use self::ui_palette::NODE_BACKGROUND;
+use self::ui_palette::{BUILDING_BUTTON_BACKGROUND, NODE_BACKGROUND, NODE_BORDER};
 
 use super::{
     assets::{HandleMap, ImageKey},
-    build::BuildAction,
-    gameplay::{GameplayManager, Resources},
-    phase::PHASE_DURATION,
-    spawn::building::BuildingType,
+    build::{BuildAction, ReadyAction},
+    gameplay::{GameplayManager, ResourceKind, Resources},
+    input::{Action, InputMap},
+    mining::cargo_capacity,
+    phase::{phase_duration, GamePhase},
+    spawn::building::BuildingCatalogue,
+    upgrades::Upgrades,
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(
         Update,
-        (update_resource_count, update_resource_gathered_count)
+        update_resource_count
             .run_if(in_state(Screen::Playing).and_then(resource_changed::<Resources>)),
     );
+    app.add_systems(
+        Update,
+        update_cargo_hold.run_if(
+            in_state(Screen::Playing)
+                .and_then(resource_changed::<Resources>.or_else(resource_changed::<Upgrades>)),
+        ),
+    );
     app.add_systems(Update, update_spinner.run_if(in_state(Screen::Playing)));
 }
 
@@ -36,10 +46,33 @@ pub struct GatherUi;
 #[derive(Component)]
 pub struct GatherResourceCountUi;
 
+/// The filled part of the cargo hold bar.
+#[derive(Component)]
+pub struct CargoBarUi;
+
 #[derive(Component)]
 pub struct CombatUi;
 
-pub fn draw_ui(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>) {
+pub fn draw_ui(
+    mut commands: Commands,
+    image_handles: Res<HandleMap<ImageKey>>,
+    catalogue: Res<BuildingCatalogue>,
+    input_map: Res<InputMap>,
+) {
+    let keys = |action| input_map.describe(action);
+    let thrust = [
+        Action::ThrustUp,
+        Action::ThrustLeft,
+        Action::ThrustDown,
+        Action::ThrustRight,
+    ]
+    .map(|action| {
+        input_map
+            .bindings(action)
+            .first()
+            .map_or("Unbound".to_string(), |binding| binding.label())
+    })
+    .join("/");
     let style = TextStyle {
         font_size: 24.0,
         color: Color::WHITE,
@@ -52,10 +85,12 @@ pub fn draw_ui(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>)
             parent.spawn((
                 Name::new("ResourceCount"),
                 ResourceCountUi,
-                TextBundle::from_sections([
-                    TextSection::new("Resources: ", style.clone()),
-                    TextSection::from_style(style.clone()),
-                ])
+                TextBundle::from_sections(ResourceKind::ALL.map(|kind| {
+                    TextSection::from_style(TextStyle {
+                        color: kind.color(),
+                        ..style.clone()
+                    })
+                }))
                 .with_style(Style {
                     position_type: PositionType::Absolute,
                     top: Val::Px(10.0),
@@ -81,10 +116,32 @@ pub fn draw_ui(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>)
                     },
                 ))
                 .with_children(|parent| {
-                    parent.tooltip_label("Left click - pan camera");
-                    parent.tooltip_label("Space/E - reset camera");
-                    parent.tooltip_label("Z - toggle zoom");
-                    parent.tooltip_label("Right click - scan for asteroids at cursor");
+                    parent.tooltip_label(format!("{} - pan camera", keys(Action::Pan)));
+                    parent.tooltip_label(format!("{} - reset camera", keys(Action::ResetCamera)));
+                    parent.tooltip_label(format!("{} - toggle zoom", keys(Action::ToggleZoom)));
+                    parent.tooltip_label(format!(
+                        "{} - scan for asteroids at cursor",
+                        keys(Action::Scan)
+                    ));
+                    parent.tooltip_label(format!(
+                        "{} - change targeting of turret at cursor",
+                        keys(Action::CycleTargeting)
+                    ));
+                    parent.tooltip_label(format!(
+                        "{} a building - inspect, repair, upgrade or sell",
+                        keys(Action::Select)
+                    ));
+                    parent.tooltip_label(
+                        "Turrets and upgrades need power - build near the station or a pylon",
+                    );
+                    parent.tooltip_label(format!(
+                        "{} - toggle grid snapping",
+                        keys(Action::ToggleSnap)
+                    ));
+                    parent.tooltip_label(format!(
+                        "{} - ready, skipping the rest of the build phase",
+                        keys(Action::Ready)
+                    ));
                 });
 
             parent
@@ -105,12 +162,22 @@ pub fn draw_ui(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>)
                     },
                 ))
                 .with_children(|parent| {
-                    parent.tooltip_label("WASD/Arrow keys - ship thrust");
-                    parent.tooltip_label("Space/E (hold) - mine asteroid below the ship");
-                    parent.tooltip_label("Right click - scan for asteroids");
+                    parent.tooltip_label(format!("{thrust} - ship thrust"));
+                    parent.tooltip_label(format!(
+                        "{} (hold) - mine asteroid below the ship",
+                        keys(Action::Mine)
+                    ));
+                    parent.tooltip_label(format!(
+                        "{} - find the nearest asteroid of each kind",
+                        keys(Action::Scan)
+                    ));
+                    parent.tooltip_label(
+                        "Asteroids yield metal, crystal or ice, shown by their tint",
+                    );
                     parent.tooltip_label(
                         "Held resources must be delivered to the base before they can be used",
                     );
+                    parent.tooltip_label("A heavily loaded ship is slower to fly");
                 });
 
             parent
@@ -131,8 +198,11 @@ pub fn draw_ui(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>)
                     },
                 ))
                 .with_children(|parent| {
-                    parent.tooltip_label("WASD/Arrow keys - ship thrust");
-                    parent.tooltip_label("Left click - fire turret at cursor position");
+                    parent.tooltip_label(format!("{thrust} - ship thrust"));
+                    parent.tooltip_label(format!(
+                        "{} - fire turret at cursor position",
+                        keys(Action::Fire)
+                    ));
                 });
 
             parent.spawn((
@@ -165,46 +235,17 @@ pub fn draw_ui(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>)
             ));
 
             parent.dock().insert(BuildUi).with_children(|parent| {
-                parent.building_button(
-                    "Decoy",
-                    "A decoy to divert enemies. Has high health to withstand attacks",
-                    BuildingType::Decoy.cost(),
-                    BuildAction {
-                        building_type: BuildingType::Decoy,
-                    },
-                );
-                parent.building_button(
-                    "Turret",
-                    "A stationary turret to shoot enemies. Has a short range and low health",
-                    BuildingType::Turret.cost(),
-                    BuildAction {
-                        building_type: BuildingType::Turret,
-                    },
-                );
-                parent.building_button(
-                    "Sniper",
-                    "A turret with a longer range and lower rate of fire",
-                    BuildingType::Sniper.cost(),
-                    BuildAction {
-                        building_type: BuildingType::Sniper,
-                    },
-                );
-                parent.building_button(
-                    "Mining Speed Upgrade",
-                    "Upgrades mining speed (so long as this building is not destroyed)",
-                    BuildingType::MiningUpgrade.cost(),
-                    BuildAction {
-                        building_type: BuildingType::MiningUpgrade,
-                    },
-                );
-                parent.building_button(
-                    "Fire Rate Upgrade",
-                    "Upgrades the combat ship's fire rate (so long as this building is not destroyed)",
-                    BuildingType::FireRateUpgrade.cost(),
-                    BuildAction {
-                        building_type: BuildingType::FireRateUpgrade,
-                    },
-                );
+                for definition in &catalogue.buildings {
+                    parent.building_button(
+                        definition.name.clone(),
+                        definition.description.clone(),
+                        definition.cost.to_string(),
+                        BuildAction {
+                            building_type: definition.id.clone(),
+                        },
+                    );
+                }
+                parent.button("Ready").insert(ReadyAction);
             });
 
             parent
@@ -217,8 +258,10 @@ pub fn draw_ui(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>)
                             style: Style {
                                 width: Val::Px(350.0),
                                 height: Val::Px(80.0),
+                                flex_direction: FlexDirection::Column,
                                 justify_content: JustifyContent::Center,
                                 align_items: AlignItems::Center,
+                                row_gap: Val::Px(8.0),
                                 ..Default::default()
                             },
                             background_color: BackgroundColor(NODE_BACKGROUND),
@@ -229,7 +272,7 @@ pub fn draw_ui(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>)
                                 Name::new("GatherResourceCount"),
                                 GatherResourceCountUi,
                                 TextBundle::from_sections([
-                                    TextSection::new("Resources held: ", style.clone()),
+                                    TextSection::new("Cargo hold: ", style.clone()),
                                     TextSection::from_style(style),
                                 ])
                                 .with_style(Style {
@@ -237,6 +280,35 @@ pub fn draw_ui(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>)
                                     ..Default::default()
                                 }),
                             ));
+                            parent
+                                .spawn((
+                                    Name::new("CargoBar"),
+                                    NodeBundle {
+                                        style: Style {
+                                            width: Val::Px(300.0),
+                                            height: Val::Px(12.0),
+                                            ..Default::default()
+                                        },
+                                        background_color: BackgroundColor(NODE_BORDER),
+                                        ..Default::default()
+                                    },
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        CargoBarUi,
+                                        NodeBundle {
+                                            style: Style {
+                                                width: Val::Percent(0.0),
+                                                height: Val::Percent(100.0),
+                                                ..Default::default()
+                                            },
+                                            background_color: BackgroundColor(
+                                                BUILDING_BUTTON_BACKGROUND,
+                                            ),
+                                            ..Default::default()
+                                        },
+                                    ));
+                                });
                         });
                 });
         });
@@ -247,27 +319,44 @@ fn update_resource_count(
     resources: Res<Resources>,
 ) {
     for (_, mut text) in query.iter_mut() {
-        text.sections[1].value = resources.delivered.to_string();
+        for (section, kind) in text.sections.iter_mut().zip(ResourceKind::ALL) {
+            section.value = format!("{}: {}   ", kind.label(), resources.delivered.get(kind));
+        }
     }
 }
 
-fn update_resource_gathered_count(
+fn update_cargo_hold(
     mut text_query: Query<&mut Text, With<GatherResourceCountUi>>,
+    mut bar_query: Query<&mut Style, With<CargoBarUi>>,
     resources: Res<Resources>,
+    upgrades: Res<Upgrades>,
 ) {
+    let capacity = cargo_capacity(&upgrades);
+    let held = resources.gathered.total();
     for mut text in text_query.iter_mut() {
-        text.sections[1].value = resources.gathered.to_string();
+        text.sections[1].value = if held == 0 {
+            format!("0 / {capacity}")
+        } else {
+            format!("{held} / {capacity} ({})", resources.gathered)
+        };
+    }
+    for mut style in bar_query.iter_mut() {
+        let fill = held as f32 / capacity as f32;
+        style.width = Val::Percent(100.0 * fill.min(1.0));
     }
 }
 
 fn update_spinner(
     mut query: Query<&mut Transform, With<SpinnerCoreUi>>,
     manager: Res<GameplayManager>,
+    phase: Res<State<GamePhase>>,
 ) {
+    // Each phase takes up a third of the spinner, however long it lasts
+    let progress =
+        (manager.current_phase_time / phase_duration(phase.get(), manager.cycle)).min(1.0);
+    let cycle_progress = (phase.get().index() as f32 + progress) / GamePhase::CYCLE.len() as f32;
     for mut transform in query.iter_mut() {
-        let total_duration = PHASE_DURATION * 3.0;
-        let angle = ((2.0 * std::f32::consts::PI) / total_duration)
-            * (total_duration - manager.elapsed_time);
+        let angle = 2.0 * std::f32::consts::PI * (1.0 - cycle_progress);
         transform.rotation = Quat::from_rotation_z(angle);
     }
 }
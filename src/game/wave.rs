@@ -0,0 +1,200 @@
+//! Enemy waves that attack at the start of each Combat phase.
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    assets::DataAsset,
+    gameplay::GameplayManager,
+    phase::GamePhase,
+    rng::GameRng,
+    spawn::enemy::{EnemyKind, SpawnEnemy},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PendingGroups>();
+    app.observe(start_wave);
+    app.add_systems(OnExit(Screen::Playing), clear_pending_groups);
+    app.add_systems(
+        Update,
+        release_groups
+            .run_if(in_state(GamePhase::Combat))
+            .in_set(AppSet::Update),
+    );
+}
+
+/// The waves for each cycle, in order. Cycles beyond the end of the script use
+/// a generated wave.
+#[derive(Asset, Resource, TypePath, Clone, Debug, Deserialize)]
+pub struct WaveScript {
+    pub waves: Vec<Wave>,
+}
+
+impl DataAsset for WaveScript {
+    const EXTENSIONS: &'static [&'static str] = &["waves.ron"];
+    const PATH: &'static str = "data/default.waves.ron";
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Wave {
+    pub groups: Vec<EnemyGroup>,
+}
+
+/// A number of enemies of the same kind arriving together.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EnemyGroup {
+    pub count: u32,
+    #[serde(default)]
+    pub kind: EnemyKind,
+    /// Multiplier on the health and damage of the group, on top of the
+    /// cycle's own multiplier.
+    #[serde(default = "default_damage_mult")]
+    pub damage_mult: f32,
+    /// Direction the group arrives from, in degrees. Random for each enemy if
+    /// not set.
+    #[serde(default)]
+    pub bearing: Option<f32>,
+    /// How widely the group is spread around its bearing, in degrees.
+    #[serde(default = "default_spread")]
+    pub spread: f32,
+    #[serde(default = "default_distance")]
+    pub distance: f32,
+    /// Seconds into the Combat phase before the group arrives.
+    #[serde(default)]
+    pub delay: f32,
+}
+
+fn default_damage_mult() -> f32 {
+    1.0
+}
+
+fn default_spread() -> f32 {
+    20.0
+}
+
+fn default_distance() -> f32 {
+    100.0
+}
+
+impl Wave {
+    /// The wave used for cycles that the script doesn't cover.
+    pub fn generate(cycle: u32) -> Self {
+        const SUPPORT_KINDS: [EnemyKind; 4] = [
+            EnemyKind::Scout,
+            EnemyKind::Brute,
+            EnemyKind::Gunship,
+            EnemyKind::Bomber,
+        ];
+
+        let count = 5 + (cycle as f32 * 2.5) as u32;
+        let group = |count, kind, delay| EnemyGroup {
+            count,
+            kind,
+            damage_mult: default_damage_mult(),
+            bearing: None,
+            spread: default_spread(),
+            distance: default_distance(),
+            delay,
+        };
+        Self {
+            groups: vec![
+                group(count - count / 3, EnemyKind::Standard, 0.0),
+                group(
+                    count / 3,
+                    SUPPORT_KINDS[cycle as usize % SUPPORT_KINDS.len()],
+                    8.0,
+                ),
+            ],
+        }
+    }
+}
+
+/// Groups of the current wave that haven't arrived yet.
+#[derive(Resource, Default, Debug)]
+pub struct PendingGroups(pub Vec<EnemyGroup>);
+
+/// Queue up the wave of the current cycle. Triggered as the Combat phase
+/// starts, but not when a saved run resumes into it, as that restores the
+/// groups still to come.
+#[derive(Event, Debug)]
+pub struct StartWave;
+
+fn start_wave(
+    _trigger: Trigger<StartWave>,
+    mut pending: ResMut<PendingGroups>,
+    manager: Res<GameplayManager>,
+    script: Res<WaveScript>,
+) {
+    let wave = match script.waves.get(manager.cycle as usize) {
+        Some(wave) => wave.clone(),
+        None => Wave::generate(manager.cycle),
+    };
+    pending.0 = wave
+        .groups
+        .into_iter()
+        .map(|group| EnemyGroup {
+            count: manager.difficulty.wave_group_size(group.count),
+            damage_mult: group.damage_mult * manager.enemy_damage_multiplier,
+            ..group
+        })
+        .collect();
+}
+
+fn release_groups(
+    mut commands: Commands,
+    mut pending: ResMut<PendingGroups>,
+    manager: Res<GameplayManager>,
+    mut rng: ResMut<GameRng>,
+) {
+    let (arrived, waiting) = std::mem::take(&mut pending.0)
+        .into_iter()
+        .partition(|group| group.delay <= manager.current_phase_time);
+    pending.0 = waiting;
+
+    for group in arrived {
+        for _ in 0..group.count {
+            let bearing = group.bearing.map(|bearing| {
+                let offset = (rng.gen::<f32>() - 0.5) * group.spread;
+                (bearing + offset).to_radians()
+            });
+            commands.trigger(SpawnEnemy {
+                distance: group.distance,
+                damage_mult: group.damage_mult,
+                bearing,
+                kind: group.kind,
+                position: None,
+                health: None,
+            });
+        }
+    }
+}
+
+fn clear_pending_groups(mut pending: ResMut<PendingGroups>) {
+    pending.0.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_waves_grow_with_cycle() {
+        let wave_size = |cycle| {
+            Wave::generate(cycle)
+                .groups
+                .iter()
+                .map(|group| group.count)
+                .sum::<u32>()
+        };
+
+        assert_eq!(wave_size(0), 5);
+        assert_eq!(wave_size(10), 30);
+        assert!(Wave::generate(10)
+            .groups
+            .iter()
+            .any(|group| group.delay > 0.0));
+    }
+}
@@ -1,31 +1,26 @@
 #[cfg(feature = "dev")]
 mod dev_tools;
-mod game;
-mod screen;
+pub mod game;
+pub mod screen;
+pub mod storage;
 mod ui;
 
 use bevy::{
     asset::AssetMetaCheck,
     audio::{AudioPlugin, Volume},
     core_pipeline::bloom::BloomSettings,
+    input::InputPlugin,
     prelude::*,
+    scene::ScenePlugin,
+    state::app::StatesPlugin,
+    window::ExitCondition,
 };
 
 pub struct AppPlugin;
 
 impl Plugin for AppPlugin {
     fn build(&self, app: &mut App) {
-        // Order new `AppStep` variants by adding them here:
-        app.configure_sets(
-            Update,
-            (
-                AppSet::TickTimers,
-                AppSet::RecordInput,
-                AppSet::Update,
-                AppSet::PostUpdate,
-            )
-                .chain(),
-        );
+        configure_app_sets(app);
 
         // Spawn the main camera.
         app.add_systems(Startup, spawn_camera);
@@ -59,6 +54,8 @@ impl Plugin for AppPlugin {
                 }),
         );
 
+        app.init_resource::<storage::Storage>();
+
         // Add other plugins.
         app.add_plugins((game::plugin, screen::plugin, ui::plugin));
 
@@ -68,9 +65,65 @@ impl Plugin for AppPlugin {
     }
 }
 
+/// Runs the game without a window, renderer or audio output, so that the
+/// game loop can be driven from integration tests.
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        configure_app_sets(app);
+
+        // Systems that raycast from the cursor expect a camera to exist.
+        app.add_systems(Startup, spawn_camera);
+
+        // Add the subset of Bevy plugins that the game logic depends on.
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            AssetPlugin::default(),
+            ScenePlugin,
+            InputPlugin,
+            TransformPlugin,
+            HierarchyPlugin,
+            WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            },
+        ));
+
+        // Asset types that are normally registered by the render and audio plugins.
+        // Without their loaders these handles never finish loading, which is fine
+        // as long as nothing waits on them.
+        app.init_asset::<Image>()
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_asset::<AudioSource>();
+
+        // Tests opt in to persistent storage explicitly.
+        app.insert_resource(storage::Storage::disabled());
+
+        app.add_plugins((game::headless_plugin, screen::plugin, ui::plugin));
+    }
+}
+
+fn configure_app_sets(app: &mut App) {
+    // Order new `AppStep` variants by adding them here:
+    app.configure_sets(
+        Update,
+        (
+            AppSet::TickTimers,
+            AppSet::RecordInput,
+            AppSet::Update,
+            AppSet::PostUpdate,
+        )
+            .chain(),
+    );
+}
+
 /// High-level groupings of systems for the app in the `Update` schedule.
 /// When adding a new variant, make sure to order it in the `configure_sets`
-/// call above.
+/// call in [`configure_app_sets`].
 #[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash)]
 enum AppSet {
     /// Tick timers.
@@ -0,0 +1,171 @@
+//! A screen for rebinding the keys, mouse buttons and gamepad buttons of each
+//! action.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::input::{Action, GamepadInput, InputBinding, InputMap, INPUT_MAP_KEY},
+    storage::Storage,
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Rebinding>();
+    app.add_systems(OnExit(Screen::Controls), stop_rebinding);
+    app.add_systems(
+        Update,
+        (capture_binding, handle_controls_action, draw_controls_menu)
+            .chain()
+            .run_if(in_state(Screen::Controls)),
+    );
+    app.register_type::<ControlsAction>();
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum ControlsAction {
+    Rebind(Action),
+    ResetDefaults,
+    Back,
+}
+
+/// The action waiting for a new binding, if any.
+#[derive(Resource, Debug, Default)]
+struct Rebinding {
+    action: Option<Action>,
+    /// Whether the left mouse button is still held from clicking "Rebind", so
+    /// that click isn't taken as the new binding.
+    awaiting_release: bool,
+}
+
+#[derive(Component)]
+struct ControlsMenu;
+
+/// Redraw the menu whenever a binding changes, which includes entering the
+/// screen.
+fn draw_controls_menu(
+    mut commands: Commands,
+    input_map: Res<InputMap>,
+    rebinding: Res<Rebinding>,
+    menu_query: Query<Entity, With<ControlsMenu>>,
+) {
+    if !input_map.is_changed() && !rebinding.is_changed() && !menu_query.is_empty() {
+        return;
+    }
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let style = TextStyle {
+        font_size: 20.0,
+        color: ui_palette::LABEL_TEXT,
+        ..Default::default()
+    };
+    commands
+        .ui_root()
+        .insert((ControlsMenu, StateScoped(Screen::Controls)))
+        .with_children(|children| {
+            children.header("Controls");
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        display: Display::Grid,
+                        grid_template_columns: RepeatedGridTrack::auto(4),
+                        column_gap: Val::Px(16.0),
+                        row_gap: Val::Px(6.0),
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|grid| {
+                    for action in Action::ALL {
+                        grid.spawn(TextBundle::from_section(
+                            format!("{}: {}", action.label(), input_map.describe(action)),
+                            style.clone(),
+                        ));
+                        let text = if rebinding.action == Some(action) {
+                            "Press a button..."
+                        } else {
+                            "Rebind"
+                        };
+                        grid.small_button(text)
+                            .insert(ControlsAction::Rebind(action));
+                    }
+                });
+            children
+                .button("Reset defaults")
+                .insert(ControlsAction::ResetDefaults);
+            children.button("Back").insert(ControlsAction::Back);
+        });
+}
+
+/// Bind the next key, mouse button or gamepad button pressed to the action
+/// being rebound.
+fn capture_binding(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: GamepadInput,
+    mut rebinding: ResMut<Rebinding>,
+    mut input_map: ResMut<InputMap>,
+    storage: Res<Storage>,
+) {
+    let Some(action) = rebinding.action else {
+        return;
+    };
+    if rebinding.awaiting_release {
+        if mouse_buttons.pressed(MouseButton::Left) {
+            return;
+        }
+        rebinding.awaiting_release = false;
+    }
+    let Some(binding) = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| InputBinding::Key(*key))
+        .or_else(|| {
+            mouse_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| InputBinding::Mouse(*button))
+        })
+        .or_else(|| gamepads.just_pressed_button().map(InputBinding::Gamepad))
+    else {
+        return;
+    };
+    input_map.rebind(action, binding);
+    storage.save(INPUT_MAP_KEY, &*input_map);
+    rebinding.action = None;
+}
+
+fn stop_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.action = None;
+}
+
+fn handle_controls_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut input_map: ResMut<InputMap>,
+    storage: Res<Storage>,
+    mut button_query: InteractionQuery<&ControlsAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                ControlsAction::Rebind(action) => {
+                    *rebinding = Rebinding {
+                        action: Some(*action),
+                        awaiting_release: true,
+                    };
+                }
+                ControlsAction::ResetDefaults => {
+                    *input_map = InputMap::default();
+                    storage.remove(INPUT_MAP_KEY);
+                    rebinding.action = None;
+                }
+                ControlsAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! A screen for choosing the difficulty before starting a new run.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        difficulty::{Difficulty, DifficultyPreset},
+        gameplay::{ResourceBundle, ResourceKind},
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (handle_difficulty_action, draw_difficulty_menu)
+            .chain()
+            .run_if(in_state(Screen::Difficulty)),
+    );
+    app.register_type::<DifficultyAction>();
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+enum DifficultyAction {
+    Preset(DifficultyPreset),
+    /// Step a custom setting up or down.
+    Adjust(DifficultySetting, i32),
+    Start,
+    Back,
+}
+
+/// How much each press changes the starting resources by.
+const STARTING_RESOURCES_STEP: ResourceBundle = ResourceBundle {
+    metal: 5,
+    crystal: 2,
+    ice: 2,
+};
+
+/// The settings that can be changed on the Custom preset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+enum DifficultySetting {
+    StartingResources,
+    StationHealth,
+    SpawnRateIncrement,
+    DamageIncrement,
+    WaveSize,
+    PeaceTime,
+}
+impl DifficultySetting {
+    const ALL: [DifficultySetting; 6] = [
+        DifficultySetting::StartingResources,
+        DifficultySetting::StationHealth,
+        DifficultySetting::SpawnRateIncrement,
+        DifficultySetting::DamageIncrement,
+        DifficultySetting::WaveSize,
+        DifficultySetting::PeaceTime,
+    ];
+
+    fn label(&self, difficulty: &Difficulty) -> String {
+        match self {
+            DifficultySetting::StartingResources => {
+                format!("Starting resources: {}", difficulty.starting_resources)
+            }
+            DifficultySetting::StationHealth => {
+                format!("Station health: {:.0}", difficulty.station_health)
+            }
+            DifficultySetting::SpawnRateIncrement => format!(
+                "Spawn rate per cycle: +{:.0}%",
+                difficulty.spawn_rate_increment * 100.0
+            ),
+            DifficultySetting::DamageIncrement => format!(
+                "Enemy damage per cycle: +{:.0}%",
+                difficulty.damage_increment * 100.0
+            ),
+            DifficultySetting::WaveSize => {
+                format!("Wave size: {:.0}%", difficulty.wave_size * 100.0)
+            }
+            DifficultySetting::PeaceTime => {
+                format!("Peace time: {:.0}s", difficulty.peace_time)
+            }
+        }
+    }
+
+    fn adjust(&self, difficulty: &mut Difficulty, steps: i32) {
+        let steps = steps as f32;
+        match self {
+            DifficultySetting::StartingResources => {
+                for kind in ResourceKind::ALL {
+                    let amount = difficulty.starting_resources.get_mut(kind);
+                    let step = STARTING_RESOURCES_STEP.get(kind) as f32;
+                    *amount = (*amount as f32 + step * steps).max(0.0) as u32;
+                }
+            }
+            DifficultySetting::StationHealth => {
+                difficulty.station_health = (difficulty.station_health + 250.0 * steps).max(250.0);
+            }
+            DifficultySetting::SpawnRateIncrement => {
+                difficulty.spawn_rate_increment =
+                    (difficulty.spawn_rate_increment + 0.05 * steps).max(0.0);
+            }
+            DifficultySetting::DamageIncrement => {
+                difficulty.damage_increment = (difficulty.damage_increment + 0.05 * steps).max(0.0);
+            }
+            DifficultySetting::WaveSize => {
+                difficulty.wave_size = (difficulty.wave_size + 0.25 * steps).max(0.25);
+            }
+            DifficultySetting::PeaceTime => {
+                difficulty.peace_time = (difficulty.peace_time + 5.0 * steps).max(0.0);
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct DifficultyMenu;
+
+/// Redraw the menu whenever the difficulty changes, which includes entering
+/// the screen.
+fn draw_difficulty_menu(
+    mut commands: Commands,
+    difficulty: Res<Difficulty>,
+    menu_query: Query<Entity, With<DifficultyMenu>>,
+) {
+    if !difficulty.is_changed() && !menu_query.is_empty() {
+        return;
+    }
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let is_custom = difficulty.preset == DifficultyPreset::Custom;
+    commands
+        .ui_root()
+        .insert((DifficultyMenu, StateScoped(Screen::Difficulty)))
+        .with_children(|children| {
+            children.header("Difficulty");
+            children.spawn(row()).with_children(|row| {
+                for preset in DifficultyPreset::ALL {
+                    let text = if preset == difficulty.preset {
+                        format!("> {} <", preset.label())
+                    } else {
+                        preset.label().to_string()
+                    };
+                    row.small_button(text)
+                        .insert(DifficultyAction::Preset(preset));
+                }
+            });
+            for setting in DifficultySetting::ALL {
+                children.spawn(row()).with_children(|row| {
+                    if is_custom {
+                        row.small_button("-")
+                            .insert(DifficultyAction::Adjust(setting, -1));
+                    }
+                    row.label(setting.label(&difficulty));
+                    if is_custom {
+                        row.small_button("+")
+                            .insert(DifficultyAction::Adjust(setting, 1));
+                    }
+                });
+            }
+            children.button("Start").insert(DifficultyAction::Start);
+            children.button("Back").insert(DifficultyAction::Back);
+        });
+}
+
+fn row() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn handle_difficulty_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut difficulty: ResMut<Difficulty>,
+    mut button_query: InteractionQuery<&DifficultyAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                DifficultyAction::Preset(DifficultyPreset::Custom) => {
+                    // Start customising from the current settings
+                    difficulty.preset = DifficultyPreset::Custom;
+                }
+                DifficultyAction::Preset(preset) => *difficulty = Difficulty::preset(*preset),
+                DifficultyAction::Adjust(setting, steps) => setting.adjust(&mut difficulty, *steps),
+                DifficultyAction::Start => next_screen.set(Screen::Playing),
+                DifficultyAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
@@ -1,8 +1,19 @@
+use std::collections::BTreeSet;
+
 use bevy::prelude::*;
 
 use super::Screen;
 use crate::{
-    game::{audio::soundtrack::PlaySoundtrack, gameplay::GameplayManager, phase::PHASE_DURATION},
+    game::{
+        audio::soundtrack::PlaySoundtrack,
+        gameplay::GameplayManager,
+        phase::cycles_elapsed,
+        rng::GameRng,
+        score::{points, unix_time, Leaderboard, LeaderboardEntry},
+        spawn::{building::BuildingCatalogue, bullet::Shooter},
+        stats::{CycleStats, RunStats},
+    },
+    storage::Storage,
     ui::prelude::*,
 };
 
@@ -23,20 +34,153 @@ enum GameOverAction {
     Back,
 }
 
-fn enter_game_over(mut commands: Commands, gameplay_manager: Res<GameplayManager>) {
-    let cycles_survived = gameplay_manager.elapsed_time / (PHASE_DURATION * 3.0);
+fn enter_game_over(
+    mut commands: Commands,
+    gameplay_manager: Res<GameplayManager>,
+    stats: Res<RunStats>,
+    catalogue: Res<BuildingCatalogue>,
+    rng: Res<GameRng>,
+    storage: Res<Storage>,
+) {
+    let cycles_survived = cycles_elapsed(gameplay_manager.elapsed_time);
+    let points = points(&stats, &gameplay_manager);
+    let rank = Leaderboard::record(
+        &storage,
+        LeaderboardEntry {
+            points,
+            cycles: cycles_survived,
+            kills: stats.total().total_kills(),
+            seed: rng.seed(),
+            difficulty: gameplay_manager.difficulty.preset,
+            timestamp: unix_time(),
+        },
+    );
     commands
         .ui_root()
         .insert(StateScoped(Screen::GameOver))
         .with_children(|children| {
             children.header("Base was destroyed");
             children.label("Game Over");
-            children.label(format!("Cycles survived: {:.1}", cycles_survived));
+            children.label(format!(
+                "Cycles survived: {:.1} - Difficulty: {}",
+                cycles_survived,
+                gameplay_manager.difficulty.preset.label()
+            ));
+            children.header(format!("Score: {points}"));
+            if let Some(rank) = rank {
+                children.label(format!("New high score! Rank {}", rank + 1));
+            }
+            stats_table(children, &stats, &catalogue);
 
             children.button("Main Menu").insert(GameOverAction::Back);
         });
 }
 
+/// The most recent cycles shown in the stats table, so that it fits on screen.
+const MAX_TABLE_CYCLES: usize = 10;
+
+/// A table of what happened in each cycle, ending with the totals.
+fn stats_table(children: &mut ChildBuilder, stats: &RunStats, catalogue: &BuildingCatalogue) {
+    // One kills column for each shooter that got a kill during the run
+    let shooters: Vec<Shooter> = stats
+        .cycles
+        .iter()
+        .flat_map(|cycle| cycle.kills.keys().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let shooter_name = |shooter: &Shooter| match shooter {
+        Shooter::CombatShip => "Ship kills".to_string(),
+        Shooter::Building(building_type) => catalogue
+            .get(building_type)
+            .map(|definition| format!("{} kills", definition.name))
+            .unwrap_or_else(|| format!("{} kills", building_type.0)),
+    };
+    let row = |label: String, cycle: &CycleStats| {
+        let mut cells = vec![label, cycle.enemies_spawned.to_string()];
+        cells.extend(shooters.iter().map(|shooter| {
+            cycle
+                .kills
+                .get(shooter)
+                .copied()
+                .unwrap_or_default()
+                .to_string()
+        }));
+        cells.extend([
+            cycle.resources_mined.to_string(),
+            cycle.resources_delivered.to_string(),
+            cycle.buildings_placed.to_string(),
+            cycle.buildings_lost.to_string(),
+            format!("{:.0}", cycle.station_damage),
+            cycle.asteroids_depleted.to_string(),
+        ]);
+        cells
+    };
+
+    let mut headings = vec!["Cycle".to_string(), "Enemies".to_string()];
+    headings.extend(shooters.iter().map(shooter_name));
+    headings.extend(
+        [
+            "Mined",
+            "Delivered",
+            "Built",
+            "Lost",
+            "Station damage",
+            "Asteroids mined out",
+        ]
+        .map(String::from),
+    );
+    let first_shown = stats.cycles.len().saturating_sub(MAX_TABLE_CYCLES);
+    let mut rows = vec![headings];
+    rows.extend(
+        stats
+            .cycles
+            .iter()
+            .enumerate()
+            .skip(first_shown)
+            .map(|(index, cycle)| row((index + 1).to_string(), cycle)),
+    );
+    rows.push(row("Total".to_string(), &stats.total()));
+
+    let columns = rows[0].len() as u16;
+    let last_row = rows.len() - 1;
+    children
+        .spawn((
+            Name::new("Stats Table"),
+            NodeBundle {
+                style: Style {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::auto(columns),
+                    column_gap: Val::Px(16.0),
+                    row_gap: Val::Px(4.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..Default::default()
+                },
+                background_color: BackgroundColor(ui_palette::NODE_BACKGROUND),
+                ..Default::default()
+            },
+        ))
+        .with_children(|table| {
+            for (index, cells) in rows.into_iter().enumerate() {
+                let color = if index == 0 || index == last_row {
+                    ui_palette::HEADER_TEXT
+                } else {
+                    ui_palette::LABEL_TEXT
+                };
+                for cell in cells {
+                    table.spawn(TextBundle::from_section(
+                        cell,
+                        TextStyle {
+                            font_size: 18.0,
+                            color,
+                            ..Default::default()
+                        },
+                    ));
+                }
+            }
+        });
+}
+
 fn exit_game_over(mut commands: Commands) {
     commands.trigger(PlaySoundtrack::Disable);
 }
@@ -0,0 +1,67 @@
+//! The best runs played on this machine, accessed from the title screen.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::score::{Leaderboard, LEADERBOARD_KEY},
+    storage::Storage,
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Leaderboard), enter_leaderboard);
+
+    app.add_systems(
+        Update,
+        handle_leaderboard_action.run_if(in_state(Screen::Leaderboard)),
+    );
+    app.register_type::<LeaderboardAction>();
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum LeaderboardAction {
+    Back,
+}
+
+fn enter_leaderboard(mut commands: Commands, storage: Res<Storage>) {
+    let leaderboard = storage
+        .load::<Leaderboard>(LEADERBOARD_KEY)
+        .unwrap_or_default();
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Leaderboard))
+        .with_children(|children| {
+            children.header("High Scores");
+            if leaderboard.entries.is_empty() {
+                children.label("No runs finished yet");
+            }
+            for (rank, entry) in leaderboard.entries.iter().enumerate() {
+                children.label(format!(
+                    "{}. {} - {} - {:.1} cycles - {} - seed {}",
+                    rank + 1,
+                    entry.points,
+                    entry.difficulty.label(),
+                    entry.cycles,
+                    entry.date(),
+                    entry.seed
+                ));
+            }
+
+            children.button("Back").insert(LeaderboardAction::Back);
+        });
+}
+
+fn handle_leaderboard_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&LeaderboardAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                LeaderboardAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
@@ -5,7 +5,11 @@ use bevy::prelude::*;
 
 use super::Screen;
 use crate::{
-    game::assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
+    game::{
+        assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
+        spawn::building::BuildingCatalogue,
+        wave::WaveScript,
+    },
     ui::prelude::*,
 };
 
@@ -31,10 +35,14 @@ fn all_assets_loaded(
     image_handles: Res<HandleMap<ImageKey>>,
     sfx_handles: Res<HandleMap<SfxKey>>,
     soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
+    building_catalogue: Option<Res<BuildingCatalogue>>,
+    wave_script: Option<Res<WaveScript>>,
 ) -> bool {
     image_handles.all_loaded(&asset_server)
         && sfx_handles.all_loaded(&asset_server)
         && soundtrack_handles.all_loaded(&asset_server)
+        && building_catalogue.is_some()
+        && wave_script.is_some()
 }
 
 fn continue_to_title(mut next_screen: ResMut<NextState<Screen>>) {
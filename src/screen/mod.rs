@@ -1,9 +1,14 @@
 //! The game's main screen states and transitions between them.
 
+mod controls;
 mod credits;
+mod difficulty;
 mod game_over;
+mod leaderboard;
 mod loading;
+mod pause;
 mod playing;
+mod settings;
 mod splash;
 mod title;
 
@@ -12,13 +17,20 @@ use bevy::prelude::*;
 pub(super) fn plugin(app: &mut App) {
     app.init_state::<Screen>();
     app.enable_state_scoped_entities::<Screen>();
+    app.add_sub_state::<Paused>();
+    app.enable_state_scoped_entities::<Paused>();
 
     app.add_plugins((
         splash::plugin,
         loading::plugin,
         title::plugin,
         credits::plugin,
+        controls::plugin,
+        difficulty::plugin,
+        leaderboard::plugin,
         playing::plugin,
+        pause::plugin,
+        settings::plugin,
         game_over::plugin,
     ));
 }
@@ -31,6 +43,15 @@ pub enum Screen {
     Loading,
     Title,
     Credits,
+    Controls,
+    Difficulty,
+    Leaderboard,
+    Settings,
     Playing,
     GameOver,
 }
+
+/// Whether the game is paused. Only exists while playing.
+#[derive(SubStates, Debug, Hash, PartialEq, Eq, Clone, Copy, Default)]
+#[source(Screen = Screen::Playing)]
+pub struct Paused(pub bool);
@@ -0,0 +1,168 @@
+//! The pause menu, which freezes the game while it is open.
+
+use bevy::{prelude::*, ui::FocusPolicy, window::WindowFocused};
+
+use super::{settings::audio_settings_panel, Paused, Screen};
+use crate::{
+    game::{
+        audio::settings::AudioSettings,
+        build::BuildLocationMarker,
+        input::{Action, Actions},
+        save::SaveRun,
+    },
+    ui::prelude::*,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    // Freezing virtual time stops the simulation, but gameplay systems would
+    // still react to input.
+    app.configure_sets(
+        Update,
+        (
+            AppSet::TickTimers,
+            AppSet::RecordInput,
+            AppSet::Update,
+            AppSet::PostUpdate,
+        )
+            .run_if(not(in_state(Paused(true)))),
+    );
+
+    app.init_resource::<PauseView>();
+    app.add_systems(OnEnter(Paused(true)), freeze_time);
+    app.add_systems(OnExit(Paused(true)), unfreeze_time);
+    app.add_systems(
+        Update,
+        (
+            // Run before build mode sees the same press.
+            toggle_pause.before(AppSet::RecordInput),
+            pause_on_focus_loss,
+        )
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(
+        Update,
+        (handle_pause_action, draw_pause_menu)
+            .chain()
+            .run_if(in_state(Paused(true))),
+    );
+    app.register_type::<PauseAction>();
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum PauseAction {
+    Resume,
+    Settings,
+    Back,
+    Quit,
+}
+
+/// Which page of the pause menu is showing.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum PauseView {
+    #[default]
+    Main,
+    Settings,
+}
+
+#[derive(Component)]
+struct PauseMenu;
+
+fn freeze_time(mut time: ResMut<Time<Virtual>>, mut view: ResMut<PauseView>) {
+    time.pause();
+    *view = PauseView::Main;
+}
+
+fn unfreeze_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+/// Cancel leaves build mode if a building is being placed, and otherwise
+/// opens or closes the pause menu.
+fn toggle_pause(
+    actions: Actions,
+    paused: Res<State<Paused>>,
+    mut next_paused: ResMut<NextState<Paused>>,
+    marker_query: Query<&BuildLocationMarker>,
+) {
+    if !actions.just_pressed(Action::Cancel) {
+        return;
+    }
+    let building = marker_query.iter().any(|marker| marker.mode.is_some());
+    if paused.0 {
+        next_paused.set(Paused(false));
+    } else if !building {
+        next_paused.set(Paused(true));
+    }
+}
+
+fn pause_on_focus_loss(
+    mut focus_reader: EventReader<WindowFocused>,
+    mut next_paused: ResMut<NextState<Paused>>,
+) {
+    if focus_reader.read().any(|event| !event.focused) {
+        next_paused.set(Paused(true));
+    }
+}
+
+/// Redraw the menu whenever the page changes, which includes pausing.
+fn draw_pause_menu(
+    mut commands: Commands,
+    view: Res<PauseView>,
+    settings: Res<AudioSettings>,
+    menu_query: Query<Entity, With<PauseMenu>>,
+) {
+    if !view.is_changed() && !menu_query.is_empty() {
+        return;
+    }
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands
+        .ui_root()
+        .insert((
+            PauseMenu,
+            StateScoped(Paused(true)),
+            BackgroundColor(Color::BLACK.with_alpha(0.6)),
+            // Keep clicks away from the HUD underneath.
+            FocusPolicy::Block,
+            ZIndex::Global(10),
+        ))
+        .with_children(|children| match *view {
+            PauseView::Main => {
+                children.header("Paused");
+                children.button("Resume").insert(PauseAction::Resume);
+                children.button("Settings").insert(PauseAction::Settings);
+                children.button("Quit to title").insert(PauseAction::Quit);
+            }
+            PauseView::Settings => {
+                children.header("Settings");
+                audio_settings_panel(children, &settings);
+                children.button("Back").insert(PauseAction::Back);
+            }
+        });
+}
+
+fn handle_pause_action(
+    mut commands: Commands,
+    mut next_paused: ResMut<NextState<Paused>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut view: ResMut<PauseView>,
+    mut button_query: InteractionQuery<&PauseAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                PauseAction::Resume => next_paused.set(Paused(false)),
+                PauseAction::Settings => *view = PauseView::Settings,
+                PauseAction::Back => *view = PauseView::Main,
+                PauseAction::Quit => {
+                    commands.trigger(SaveRun);
+                    next_screen.set(Screen::Title);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! A screen for changing the volume, and the audio settings panel it shares
+//! with other menus.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::audio::settings::{AudioSettings, AUDIO_SETTINGS_KEY},
+    storage::Storage,
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Settings), enter_settings);
+    app.add_systems(
+        Update,
+        handle_settings_action.run_if(in_state(Screen::Settings)),
+    );
+
+    // The panel can appear on any screen, so it isn't tied to this one.
+    app.add_systems(
+        Update,
+        (
+            handle_audio_settings_action,
+            apply_volume_sliders,
+            update_audio_settings_labels,
+        )
+            .chain(),
+    );
+    app.register_type::<SettingsAction>();
+    app.register_type::<AudioSettingsAction>();
+    app.register_type::<VolumeSlider>();
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum SettingsAction {
+    Back,
+}
+
+fn enter_settings(mut commands: Commands, settings: Res<AudioSettings>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Settings))
+        .with_children(|children| {
+            children.header("Settings");
+            audio_settings_panel(children, &settings);
+            children.button("Back").insert(SettingsAction::Back);
+        });
+}
+
+fn handle_settings_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&SettingsAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                SettingsAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
+
+/// A volume that can be set with a slider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+enum VolumeChannel {
+    Master,
+    Music,
+    Sfx,
+}
+impl VolumeChannel {
+    const ALL: [VolumeChannel; 3] = [
+        VolumeChannel::Master,
+        VolumeChannel::Music,
+        VolumeChannel::Sfx,
+    ];
+
+    fn volume_mut<'a>(&self, settings: &'a mut AudioSettings) -> &'a mut f32 {
+        match self {
+            VolumeChannel::Master => &mut settings.master,
+            VolumeChannel::Music => &mut settings.music,
+            VolumeChannel::Sfx => &mut settings.sfx,
+        }
+    }
+
+    fn volume(&self, settings: &AudioSettings) -> f32 {
+        match self {
+            VolumeChannel::Master => settings.master,
+            VolumeChannel::Music => settings.music,
+            VolumeChannel::Sfx => settings.sfx,
+        }
+    }
+
+    fn label(&self, settings: &AudioSettings) -> String {
+        let name = match self {
+            VolumeChannel::Master => "Master",
+            VolumeChannel::Music => "Music",
+            VolumeChannel::Sfx => "Sound effects",
+        };
+        format!("{name}: {:.0}%", self.volume(settings) * 100.0)
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+enum AudioSettingsAction {
+    ToggleMute,
+}
+
+/// The slider controlling a volume.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+struct VolumeSlider(VolumeChannel);
+
+/// Text showing the current value of an audio setting.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+enum AudioSettingsLabel {
+    Volume(VolumeChannel),
+    Mute,
+}
+impl AudioSettingsLabel {
+    fn text(&self, settings: &AudioSettings) -> String {
+        match self {
+            AudioSettingsLabel::Volume(channel) => channel.label(settings),
+            AudioSettingsLabel::Mute if settings.muted => "Unmute".to_string(),
+            AudioSettingsLabel::Mute => "Mute".to_string(),
+        }
+    }
+}
+
+/// Volume sliders and a mute toggle. Changes are applied straight away, and
+/// saved once a slider is let go or the mute button is pressed.
+pub(super) fn audio_settings_panel(children: &mut ChildBuilder, settings: &AudioSettings) {
+    for channel in VolumeChannel::ALL {
+        children
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|row| {
+                row.label(channel.label(settings))
+                    .insert(AudioSettingsLabel::Volume(channel));
+                row.slider(channel.volume(settings))
+                    .insert(VolumeSlider(channel));
+            });
+    }
+    let mute = AudioSettingsLabel::Mute;
+    children
+        .button(mute.text(settings))
+        .insert((AudioSettingsAction::ToggleMute, mute));
+}
+
+fn handle_audio_settings_action(
+    mut settings: ResMut<AudioSettings>,
+    storage: Res<Storage>,
+    mut button_query: InteractionQuery<&AudioSettingsAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                AudioSettingsAction::ToggleMute => settings.muted = !settings.muted,
+            }
+            storage.save(AUDIO_SETTINGS_KEY, &*settings);
+        }
+    }
+}
+
+/// Follow the sliders while they are dragged, but only save once they are let
+/// go, rather than on every frame of the drag.
+fn apply_volume_sliders(
+    mut settings: ResMut<AudioSettings>,
+    storage: Res<Storage>,
+    slider_query: Query<(&Slider, &VolumeSlider), Changed<Slider>>,
+    mut release_query: InteractionQuery<&VolumeSlider>,
+    mut unsaved: Local<bool>,
+) {
+    for (slider, VolumeSlider(channel)) in &slider_query {
+        if channel.volume(&settings) != slider.value {
+            *channel.volume_mut(&mut settings) = slider.value;
+            *unsaved = true;
+        }
+    }
+    let released = release_query
+        .iter_mut()
+        .any(|(interaction, _)| !matches!(interaction, Interaction::Pressed));
+    if *unsaved && released {
+        storage.save(AUDIO_SETTINGS_KEY, &*settings);
+        *unsaved = false;
+    }
+}
+
+/// Labels and buttons keep their text in a child entity.
+fn update_audio_settings_labels(
+    settings: Res<AudioSettings>,
+    label_query: Query<(&AudioSettingsLabel, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (label, children) in &label_query {
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = label.text(&settings);
+        }
+    }
+}
@@ -7,7 +7,10 @@ use crate::{
     game::{
         assets::{HandleMap, ImageKey, SoundtrackKey},
         audio::soundtrack::PlaySoundtrack,
+        rng::GameRng,
+        save::{Resume, SavedRun, SAVE_KEY},
     },
+    storage::Storage,
     ui::prelude::*,
 };
 
@@ -23,14 +26,22 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 #[reflect(Component)]
 enum TitleAction {
+    Continue,
     Play,
+    Leaderboard,
+    Controls,
+    Settings,
     Credits,
     /// Exit doesn't work well with embedded applications.
     #[cfg(not(target_family = "wasm"))]
     Exit,
 }
 
-fn enter_title(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>) {
+fn enter_title(
+    mut commands: Commands,
+    image_handles: Res<HandleMap<ImageKey>>,
+    storage: Res<Storage>,
+) {
     commands
         .ui_root()
         .insert(StateScoped(Screen::Title))
@@ -45,7 +56,15 @@ fn enter_title(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>)
                 image: UiImage::new(image_handles.get(&ImageKey::Title).unwrap().clone()),
                 ..Default::default()
             },));
+            if storage.contains(SAVE_KEY) {
+                children.button("Continue").insert(TitleAction::Continue);
+            }
             children.button("Play").insert(TitleAction::Play);
+            children
+                .button("High Scores")
+                .insert(TitleAction::Leaderboard);
+            children.button("Controls").insert(TitleAction::Controls);
+            children.button("Settings").insert(TitleAction::Settings);
             children.button("Credits").insert(TitleAction::Credits);
 
             #[cfg(not(target_family = "wasm"))]
@@ -56,14 +75,27 @@ fn enter_title(mut commands: Commands, image_handles: Res<HandleMap<ImageKey>>)
 }
 
 fn handle_title_action(
+    mut commands: Commands,
     mut next_screen: ResMut<NextState<Screen>>,
+    storage: Res<Storage>,
+    mut rng: ResMut<GameRng>,
     mut button_query: InteractionQuery<&TitleAction>,
     #[cfg(not(target_family = "wasm"))] mut app_exit: EventWriter<AppExit>,
 ) {
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
             match action {
-                TitleAction::Play => next_screen.set(Screen::Playing),
+                TitleAction::Continue => {
+                    if let Some(saved_run) = storage.load::<SavedRun>(SAVE_KEY) {
+                        rng.replay_seed = Some(saved_run.seed);
+                        commands.insert_resource(Resume(saved_run));
+                        next_screen.set(Screen::Playing);
+                    }
+                }
+                TitleAction::Play => next_screen.set(Screen::Difficulty),
+                TitleAction::Leaderboard => next_screen.set(Screen::Leaderboard),
+                TitleAction::Controls => next_screen.set(Screen::Controls),
+                TitleAction::Settings => next_screen.set(Screen::Settings),
                 TitleAction::Credits => next_screen.set(Screen::Credits),
 
                 #[cfg(not(target_family = "wasm"))]
@@ -0,0 +1,84 @@
+//! Persistent storage for data that outlives a single session, such as saved
+//! runs. Each entry is stored as a RON file named after its key.
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Where persistent data is kept. Storage is disabled on the web, where there
+/// is no file system to write to.
+#[derive(Resource, Debug, Clone)]
+pub struct Storage {
+    dir: Option<PathBuf>,
+}
+impl Storage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: Some(dir.into()),
+        }
+    }
+
+    /// Storage that never persists anything.
+    pub fn disabled() -> Self {
+        Self { dir: None }
+    }
+
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(format!("{key}.ron")))
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.path(key).is_some_and(|path| path.exists())
+    }
+
+    /// Read the value stored under `key`, if there is one and it can be parsed.
+    pub fn load<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let path = self.path(key)?;
+        let contents = std::fs::read_to_string(&path).ok()?;
+        match ron::from_str(&contents) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                warn!("Failed to parse {}: {error}", path.display());
+                None
+            }
+        }
+    }
+
+    pub fn save<T: Serialize>(&self, key: &str, value: &T) {
+        let Some(path) = self.path(key) else {
+            return;
+        };
+        let result = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+                }
+                std::fs::write(&path, contents).map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            warn!("Failed to write {}: {error}", path.display());
+        }
+    }
+
+    pub fn remove(&self, key: &str) {
+        if let Some(path) = self.path(key) {
+            if path.exists() {
+                if let Err(error) = std::fs::remove_file(&path) {
+                    warn!("Failed to remove {}: {error}", path.display());
+                }
+            }
+        }
+    }
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        if cfg!(target_family = "wasm") {
+            Self::disabled()
+        } else {
+            Self::new("save")
+        }
+    }
+}
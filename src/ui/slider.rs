@@ -0,0 +1,58 @@
+//! Sliders that can be clicked or dragged along their track.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Slider>();
+    app.add_systems(Update, (drag_sliders, update_slider_fills).chain());
+}
+
+/// A value between 0 and 1, set by pressing on the slider's track and dragging
+/// along it.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Slider {
+    pub value: f32,
+}
+
+/// The part of the track filled up to the slider's value.
+#[derive(Component, Debug)]
+pub struct SliderFill;
+
+/// The track stays pressed while the mouse button is held, even once the
+/// cursor has left it, so dragging past either end pins the value there.
+fn drag_sliders(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut slider_query: Query<(&mut Slider, &Interaction, &Node, &GlobalTransform)>,
+) {
+    let Some(cursor) = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+    for (mut slider, interaction, node, transform) in &mut slider_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        let track = node.logical_rect(transform);
+        if track.width() <= 0.0 {
+            continue;
+        }
+        let value = ((cursor.x - track.min.x) / track.width()).clamp(0.0, 1.0);
+        slider.set_if_neq(Slider { value });
+    }
+}
+
+fn update_slider_fills(
+    slider_query: Query<(&Slider, &Children), Changed<Slider>>,
+    mut fill_query: Query<&mut Style, With<SliderFill>>,
+) {
+    for (slider, children) in &slider_query {
+        let mut fills = fill_query.iter_many_mut(children);
+        while let Some(mut style) = fills.fetch_next() {
+            style.width = Val::Percent(slider.value * 100.0);
+        }
+    }
+}
@@ -2,13 +2,20 @@
 
 use bevy::{ecs::system::EntityCommands, prelude::*, ui::Val::*};
 
-use super::{interaction::InteractionPalette, palette::*};
+use super::{
+    interaction::InteractionPalette,
+    palette::*,
+    slider::{Slider, SliderFill},
+};
 
 /// An extension trait for spawning UI widgets.
 pub trait Widgets {
     /// Spawn a simple button with text.
     fn button(&mut self, text: impl Into<String>) -> EntityCommands;
 
+    /// Spawn a button with text that is small enough to fit in a panel.
+    fn small_button(&mut self, text: impl Into<String>) -> EntityCommands;
+
     /// Spawn a simple header label. Bigger than [`Widgets::label`].
     fn header(&mut self, text: impl Into<String>) -> EntityCommands;
 
@@ -17,13 +24,16 @@ pub trait Widgets {
 
     fn tooltip_label(&mut self, text: impl Into<String>) -> EntityCommands;
 
+    /// Spawn a [`Slider`] track, filled up to `value`.
+    fn slider(&mut self, value: f32) -> EntityCommands;
+
     fn dock(&mut self) -> EntityCommands;
 
     fn building_button(
         &mut self,
         name: impl Into<String>,
         description: impl Into<String>,
-        cost: u32,
+        cost: impl Into<String>,
         component: impl Component,
     ) -> EntityCommands;
 }
@@ -65,6 +75,42 @@ impl<T: Spawn> Widgets for T {
         entity
     }
 
+    fn small_button(&mut self, text: impl Into<String>) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Button"),
+            ButtonBundle {
+                style: Style {
+                    width: Px(200.0),
+                    height: Px(40.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(BUILDING_BUTTON_BACKGROUND),
+                ..default()
+            },
+            InteractionPalette {
+                none: BUILDING_BUTTON_BACKGROUND,
+                hovered: BUTTON_HOVERED_BACKGROUND,
+                pressed: BUTTON_PRESSED_BACKGROUND,
+            },
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Button Text"),
+                TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font_size: 20.0,
+                        color: BUTTON_TEXT,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+        entity
+    }
+
     fn header(&mut self, text: impl Into<String>) -> EntityCommands {
         let mut entity = self.spawn((
             Name::new("Header"),
@@ -154,6 +200,43 @@ impl<T: Spawn> Widgets for T {
         entity
     }
 
+    fn slider(&mut self, value: f32) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Slider"),
+            ButtonBundle {
+                style: Style {
+                    width: Px(200.0),
+                    height: Px(20.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(NODE_BACKGROUND),
+                ..default()
+            },
+            InteractionPalette {
+                none: NODE_BACKGROUND,
+                hovered: BUTTON_HOVERED_BACKGROUND,
+                pressed: BUTTON_HOVERED_BACKGROUND,
+            },
+            Slider { value },
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Slider Fill"),
+                SliderFill,
+                NodeBundle {
+                    style: Style {
+                        width: Percent(value * 100.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(BUILDING_BUTTON_BACKGROUND),
+                    ..default()
+                },
+            ));
+        });
+        entity
+    }
+
     fn dock(&mut self) -> EntityCommands {
         self.spawn((
             Name::new("Dock"),
@@ -180,7 +263,7 @@ impl<T: Spawn> Widgets for T {
         &mut self,
         name: impl Into<String>,
         description: impl Into<String>,
-        cost: u32,
+        cost: impl Into<String>,
         component: impl Component,
     ) -> EntityCommands {
         let mut entity = self.spawn((
@@ -231,7 +314,7 @@ impl<T: Spawn> Widgets for T {
             children.spawn((
                 Name::new("Building Cost"),
                 TextBundle::from_section(
-                    format!("Cost: {}", cost),
+                    format!("Cost: {}", cost.into()),
                     TextStyle {
                         font_size: 12.0,
                         color: LABEL_TEXT,
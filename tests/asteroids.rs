@@ -0,0 +1,84 @@
+//! Generating the asteroid field.
+
+mod common;
+
+use bevy::prelude::*;
+use tri_phase_defense::game::spawn::{
+    asteroid::Asteroid,
+    asteroid_field::{AsteroidField, SpawnRandomAsteroid, MAX_ASTEROIDS},
+};
+
+use common::*;
+
+#[test]
+fn same_seed_spawns_same_asteroids() {
+    let asteroid_positions = |seed| {
+        let mut app = headless_app(seed);
+        let mut positions: Vec<_> = app
+            .world_mut()
+            .query_filtered::<&Transform, With<Asteroid>>()
+            .iter(app.world())
+            .map(|transform| transform.translation.to_array())
+            .collect();
+        positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        positions
+    };
+
+    assert_eq!(asteroid_positions(4), asteroid_positions(4));
+    assert_ne!(asteroid_positions(4), asteroid_positions(5));
+}
+
+#[test]
+fn asteroid_fields_are_seeded_clustered_and_capped() {
+    fn asteroids(app: &mut App) -> Vec<(Vec3, f32, u32)> {
+        app.world_mut()
+            .query::<(&Asteroid, &Transform)>()
+            .iter(app.world())
+            .map(|(asteroid, transform)| {
+                (
+                    transform.translation,
+                    asteroid.radius,
+                    asteroid.max_resources(),
+                )
+            })
+            .collect()
+    }
+
+    // The same seed generates the same field
+    let mut app = headless_app(25);
+    let field = asteroids(&mut app);
+    assert_eq!(asteroids(&mut headless_app(25)), field);
+    assert_ne!(asteroids(&mut headless_app(26)), field);
+
+    // Asteroids vary in size and form clusters around the belts
+    let radii: Vec<_> = field.iter().map(|&(_, radius, _)| radius).collect();
+    assert!(radii.iter().any(|&radius| radius != radii[0]));
+    let belts = app.world().resource::<AsteroidField>().belts.clone();
+    let in_belt = |position: Vec3| {
+        belts
+            .iter()
+            .any(|belt| belt.center.distance(position.xy()) <= belt.spread + 1e-3)
+    };
+    assert!(
+        field
+            .iter()
+            .filter(|&&(position, _, _)| in_belt(position))
+            .count()
+            >= 18
+    );
+
+    // Belts further out are richer
+    let mut belts_by_distance = belts.clone();
+    belts_by_distance.sort_by(|a, b| a.center.length().total_cmp(&b.center.length()));
+    assert!(belts_by_distance
+        .windows(2)
+        .all(|pair| pair[0].richness <= pair[1].richness));
+    assert!(belts_by_distance[0].richness < belts_by_distance[5].richness);
+
+    // Replacements stop once the field is full
+    for _ in 0..MAX_ASTEROIDS {
+        app.world_mut().trigger(SpawnRandomAsteroid);
+        app.update();
+    }
+    assert_eq!(count::<Asteroid>(&mut app), MAX_ASTEROIDS);
+}
@@ -0,0 +1,213 @@
+//! Placing, upgrading, repairing and selling buildings, and powering them.
+
+mod common;
+
+use bevy::prelude::*;
+use tri_phase_defense::game::{
+    gameplay::{ResourceBundle, ResourceKind, Resources},
+    inspector::{RepairBuilding, SellBuilding},
+    notifications::Notification,
+    power::Powered,
+    spawn::{
+        asteroid::{asteroid_yield, nearest_asteroids_report, Asteroid, SpawnAsteroid},
+        building::{Building, Destructable, UpgradeBuilding},
+    },
+    upgrades::Upgrades,
+};
+
+use common::*;
+
+#[test]
+fn upgrade_buildings_count_towards_upgrades() {
+    let mut app = headless_app(3);
+
+    let building = spawn_building(&mut app, "mining_upgrade", Vec3::new(30.0, 0.0, 0.0));
+
+    assert_eq!(count::<Destructable>(&mut app), 2);
+    assert_eq!(app.world().resource::<Upgrades>().mining_speed, 1);
+    assert_eq!(app.world().resource::<Upgrades>().fire_rate, 0);
+
+    // Higher tiers count as more upgrades
+    app.world_mut().resource_mut::<Resources>().delivered = ResourceBundle {
+        metal: 100,
+        crystal: 100,
+        ice: 100,
+    };
+    app.world_mut().trigger(UpgradeBuilding(building));
+    app.update();
+    assert_eq!(app.world().resource::<Upgrades>().mining_speed, 2);
+}
+
+#[test]
+fn buildings_can_be_repaired_and_sold() {
+    let mut app = headless_app(10);
+    let building =
+        spawn_damaged_building(&mut app, "mining_upgrade", Vec3::new(30.0, 0.0, 0.0), 30.0);
+    assert_eq!(app.world().resource::<Upgrades>().mining_speed, 1);
+
+    // Half of the mining upgrade's health is missing
+    app.world_mut().trigger(RepairBuilding(building));
+    app.update();
+    assert_eq!(
+        app.world().get::<Destructable>(building).unwrap().health,
+        60.0
+    );
+    // A quarter of the mining upgrade's cost, rounded up
+    assert_eq!(
+        app.world().resource::<Resources>().delivered,
+        ResourceBundle {
+            metal: 10 - 2,
+            crystal: 4,
+            ice: 4 - 2
+        }
+    );
+
+    // Repairing at full health is refused
+    app.world_mut().trigger(RepairBuilding(building));
+    let notifications = app.world().resource::<Events<Notification>>();
+    assert!(notifications
+        .get_reader()
+        .read(notifications)
+        .any(|Notification(text)| text == "Already at full health"));
+
+    app.world_mut().trigger(SellBuilding(building));
+    app.update();
+    assert_eq!(count::<Building>(&mut app), 0);
+    assert_eq!(app.world().resource::<Upgrades>().mining_speed, 0);
+    assert_eq!(
+        app.world().resource::<Resources>().delivered,
+        ResourceBundle {
+            metal: 10 - 2 + 4,
+            crystal: 4,
+            ice: 4 - 2 + 3
+        }
+    );
+}
+
+#[test]
+fn turrets_can_be_upgraded_to_their_last_tier() {
+    let mut app = headless_app(11);
+    app.world_mut().resource_mut::<Resources>().delivered = ResourceBundle {
+        metal: 30,
+        crystal: 20,
+        ice: 0,
+    };
+    let building = spawn_damaged_building(&mut app, "turret", Vec3::new(30.0, 0.0, 0.0), 40.0);
+
+    for _ in 0..3 {
+        app.world_mut().trigger(UpgradeBuilding(building));
+        app.update();
+    }
+    // The third upgrade is refused, as the turret only has two tiers
+    assert_eq!(app.world().get::<Building>(building).unwrap().level, 2);
+    assert_eq!(
+        app.world().resource::<Resources>().delivered,
+        ResourceBundle {
+            metal: 30 - 6 - 10,
+            crystal: 20 - 4 - 6,
+            ice: 0
+        }
+    );
+    // Damage taken before the upgrades is kept
+    let destructable = app.world().get::<Destructable>(building).unwrap();
+    assert_eq!(destructable.max_health(), 120.0);
+    assert_eq!(destructable.health, 110.0);
+
+    // The refund covers the upgrades as well as the turret itself: half of
+    // 24 metal and 14 crystal, scaled by the remaining health of 110 / 120
+    app.world_mut().trigger(SellBuilding(building));
+    app.update();
+    assert_eq!(
+        app.world().resource::<Resources>().delivered,
+        ResourceBundle {
+            metal: 14 + 11,
+            crystal: 10 + 6,
+            ice: 0
+        }
+    );
+}
+
+#[test]
+fn buildings_only_work_when_connected_to_the_power_grid() {
+    let mut app = headless_app(22);
+
+    // Out of reach of the station
+    let upgrade = spawn_building(&mut app, "mining_upgrade", Vec3::new(70.0, 0.0, 0.0));
+    assert!(!app.world().entity(upgrade).contains::<Powered>());
+    assert_eq!(app.world().resource::<Upgrades>().mining_speed, 0);
+
+    // A pylon in reach of the station bridges the gap
+    let pylon = spawn_building(&mut app, "pylon", Vec3::new(42.0, 0.0, 0.0));
+    app.update();
+    assert!(app.world().entity(pylon).contains::<Powered>());
+    assert!(app.world().entity(upgrade).contains::<Powered>());
+    assert_eq!(app.world().resource::<Upgrades>().mining_speed, 1);
+
+    // Losing the pylon cuts the upgrade off again
+    app.world_mut().despawn(pylon);
+    app.update();
+    assert!(!app.world().entity(upgrade).contains::<Powered>());
+    assert_eq!(app.world().resource::<Upgrades>().mining_speed, 0);
+}
+
+#[test]
+fn cargo_upgrades_enlarge_the_hold() {
+    let mut app = headless_app(23);
+    spawn_building(&mut app, "cargo_upgrade", Vec3::new(35.0, 0.0, 0.0));
+    assert_eq!(app.world().resource::<Upgrades>().cargo_capacity, 1);
+}
+
+#[test]
+fn costs_need_every_kind_of_resource() {
+    let mut app = headless_app(24);
+    app.world_mut().resource_mut::<Resources>().delivered =
+        ResourceBundle::new(ResourceKind::Metal, 50);
+    let building = spawn_building(&mut app, "turret", Vec3::new(30.0, 0.0, 0.0));
+
+    // Plenty of metal doesn't make up for missing crystal
+    app.world_mut().trigger(UpgradeBuilding(building));
+    app.update();
+    assert_eq!(app.world().get::<Building>(building).unwrap().level, 0);
+    assert_eq!(
+        app.world().resource::<Resources>().delivered,
+        ResourceBundle::new(ResourceKind::Metal, 50)
+    );
+
+    app.world_mut()
+        .resource_mut::<Resources>()
+        .delivered
+        .crystal = 4;
+    app.world_mut().trigger(UpgradeBuilding(building));
+    app.update();
+    assert_eq!(app.world().get::<Building>(building).unwrap().level, 1);
+    assert_eq!(
+        app.world().resource::<Resources>().delivered,
+        ResourceBundle::new(ResourceKind::Metal, 44)
+    );
+
+    // Scans report the nearest asteroid of each kind
+    let position = Vec3::new(400.0, 0.0, -5.0);
+    app.world_mut().trigger(SpawnAsteroid {
+        position,
+        kind: ResourceKind::Crystal,
+        radius: 7.0,
+        max_resources: 8,
+        is_visible: true,
+        contained_resources: None,
+    });
+    app.update();
+    let mut asteroid_query = app.world_mut().query::<(&Asteroid, &Transform)>();
+    let asteroids: Vec<_> = asteroid_query.iter(app.world()).collect();
+    let (crystal, _) = asteroids
+        .iter()
+        .find(|(_, transform)| transform.translation == position)
+        .unwrap();
+    assert_eq!(crystal.kind, ResourceKind::Crystal);
+    // Crystal is the scarcest kind
+    assert!(
+        asteroid_yield(ResourceKind::Crystal, crystal.radius, 1.0)
+            < asteroid_yield(ResourceKind::Metal, crystal.radius, 1.0)
+    );
+    let report = nearest_asteroids_report(position.xy(), asteroids);
+    assert!(report.contains("Crystal 0 units"), "{report}");
+}
@@ -0,0 +1,109 @@
+//! Enemies, the damage they deal and the kills recorded against them.
+
+mod common;
+
+use bevy::prelude::*;
+use tri_phase_defense::game::{
+    difficulty::{Difficulty, DifficultyPreset},
+    gameplay::GameplayManager,
+    phase::GamePhase,
+    score::points,
+    spawn::{
+        building::{BuildingType, Destructable},
+        bullet::Shooter,
+        enemy::{Enemy, EnemyKilled, EnemyKind, SpawnEnemy},
+        station::Station,
+    },
+    stats::RunStats,
+};
+
+use common::*;
+
+#[test]
+fn bombers_dive_at_the_station_and_explode() {
+    let mut app = headless_app(9);
+    spawn_building(&mut app, "decoy", Vec3::new(30.0, 0.0, 0.0));
+    app.world_mut().trigger(SpawnEnemy {
+        distance: 40.0,
+        damage_mult: 1.0,
+        bearing: Some(0.0),
+        kind: EnemyKind::Bomber,
+        position: None,
+        health: None,
+    });
+    app.update();
+    assert_eq!(count::<Enemy>(&mut app), 1);
+
+    for _ in 0..50 {
+        app.update();
+    }
+
+    // The bomber flies past the closer decoy to reach the station
+    assert_eq!(count::<Enemy>(&mut app), 0);
+    let station_health = app
+        .world_mut()
+        .query_filtered::<&Destructable, With<Station>>()
+        .single(app.world())
+        .health;
+    assert!(station_health < 2000.0);
+    let decoy_health = app
+        .world_mut()
+        .query_filtered::<&Destructable, Without<Station>>()
+        .single(app.world())
+        .health;
+    assert_eq!(decoy_health, 200.0);
+}
+
+#[test]
+fn enemies_hit_harder_on_harder_difficulties() {
+    let second_wave_damage = |preset| {
+        let mut app = loaded_app(15);
+        app.insert_resource(Difficulty::preset(preset));
+        start_playing(&mut app);
+        // Skip to the second cycle, where the difficulties have diverged
+        app.world_mut()
+            .resource_mut::<GameplayManager>()
+            .new_cycle();
+        run_until_phase(&mut app, GamePhase::Combat);
+        app.update();
+        app.world_mut()
+            .query::<&Enemy>()
+            .iter(app.world())
+            .filter(|enemy| enemy.kind == EnemyKind::Standard)
+            .map(|enemy| enemy.damage)
+            .fold(0.0, f32::max)
+    };
+
+    let hard = second_wave_damage(DifficultyPreset::Hard);
+    let easy = second_wave_damage(DifficultyPreset::Easy);
+    assert!(easy > 0.0);
+    assert!(hard > easy);
+}
+
+#[test]
+fn kills_are_recorded_per_cycle_and_shooter() {
+    let mut app = headless_app(14);
+    let turret = Shooter::Building(BuildingType("turret".to_string()));
+    app.world_mut().trigger(EnemyKilled {
+        kind: EnemyKind::Scout,
+        shooter: turret.clone(),
+    });
+    run_until_phase(&mut app, GamePhase::Combat);
+    run_until_phase(&mut app, GamePhase::Build);
+    app.update();
+    app.world_mut().trigger(EnemyKilled {
+        kind: EnemyKind::Standard,
+        shooter: Shooter::CombatShip,
+    });
+    app.update();
+
+    let stats = app.world().resource::<RunStats>().clone();
+    assert_eq!(stats.cycles.len(), 2);
+    assert_eq!(stats.cycles[0].kills.get(&turret), Some(&1));
+    assert!(stats.cycles[0].enemies_spawned >= 5);
+    assert_eq!(stats.cycles[1].kills.get(&Shooter::CombatShip), Some(&1));
+    assert_eq!(stats.total().total_kills(), 2);
+
+    let manager = app.world().resource::<GameplayManager>();
+    assert!(points(&stats, manager) >= 1000 + 2 * 10);
+}
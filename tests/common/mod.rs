@@ -0,0 +1,123 @@
+//! Helpers for driving the full game loop without a window, renderer or audio
+//! output.
+
+// Each test file is its own crate and only uses some of these.
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use tri_phase_defense::{
+    game::{
+        phase::GamePhase,
+        rng::GameRng,
+        spawn::building::{Building, BuildingCatalogue, BuildingType, SpawnBuilding},
+        wave::WaveScript,
+    },
+    screen::Screen,
+    storage::Storage,
+    HeadlessPlugin,
+};
+
+/// How much time passes on every call to [`App::update`].
+const TIME_STEP: Duration = Duration::from_millis(100);
+
+/// Upper bound on the number of updates to wait for a phase change.
+const MAX_UPDATES_PER_PHASE: usize = 1000;
+
+/// Upper bound on the number of updates to wait for the game data to load.
+const MAX_UPDATES_FOR_DATA: usize = 500;
+
+/// Build a headless app and wait for the game data to load.
+pub fn loaded_app(seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(TIME_STEP));
+    app.world_mut().resource_mut::<GameRng>().replay_seed = Some(seed);
+
+    // The game data is loaded asynchronously, just like in the real game.
+    let data_loaded = |app: &App| {
+        app.world().contains_resource::<BuildingCatalogue>()
+            && app.world().contains_resource::<WaveScript>()
+    };
+    for _ in 0..MAX_UPDATES_FOR_DATA {
+        if data_loaded(&app) {
+            break;
+        }
+        app.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert!(data_loaded(&app), "Timed out loading the game data");
+    app
+}
+
+/// Skip straight to [`Screen::Playing`], bypassing the loading screen which
+/// waits on assets that can't load without a renderer.
+pub fn start_playing(app: &mut App) {
+    app.world_mut()
+        .resource_mut::<NextState<Screen>>()
+        .set(Screen::Playing);
+    app.update();
+}
+
+pub fn headless_app(seed: u64) -> App {
+    let mut app = loaded_app(seed);
+    start_playing(&mut app);
+    app
+}
+
+/// Storage in a fresh temporary directory, so tests don't share save files.
+pub fn temp_storage(name: &str) -> Storage {
+    let dir = std::env::temp_dir().join(format!("tri-phase-defense-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    Storage::new(dir)
+}
+
+pub fn current_phase(app: &App) -> GamePhase {
+    app.world().resource::<State<GamePhase>>().get().clone()
+}
+
+pub fn run_until_phase(app: &mut App, phase: GamePhase) {
+    for _ in 0..MAX_UPDATES_PER_PHASE {
+        if current_phase(app) == phase {
+            return;
+        }
+        app.update();
+    }
+    panic!("Timed out waiting for {phase:?}");
+}
+
+pub fn count<C: Component>(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), With<C>>()
+        .iter(app.world())
+        .count()
+}
+
+/// Spawn a building at full health and return it once it's in the world.
+pub fn spawn_building(app: &mut App, kind: &str, position: Vec3) -> Entity {
+    spawn(app, kind, position, None)
+}
+
+/// Spawn a building with only `health` left and return it once it's in the
+/// world.
+pub fn spawn_damaged_building(app: &mut App, kind: &str, position: Vec3, health: f32) -> Entity {
+    spawn(app, kind, position, Some(health))
+}
+
+fn spawn(app: &mut App, kind: &str, position: Vec3, health: Option<f32>) -> Entity {
+    app.world_mut().trigger(SpawnBuilding {
+        building_type: BuildingType(kind.to_string()),
+        position,
+        health,
+        level: 0,
+        targeting: None,
+    });
+    app.update();
+    app.world_mut()
+        .query_filtered::<(Entity, &Transform), With<Building>>()
+        .iter(app.world())
+        .find(|(_, transform)| transform.translation == position)
+        .expect("The building should have been spawned")
+        .0
+}
@@ -0,0 +1,142 @@
+//! Moving a run through the phases of each cycle.
+
+mod common;
+
+use bevy::prelude::*;
+use tri_phase_defense::{
+    game::{
+        difficulty::{Difficulty, DifficultyPreset},
+        gameplay::{GameplayManager, ResourceBundle, Resources},
+        phase::{phase_duration, GamePhase, SkipBuildPhase},
+        spawn::{asteroid::Asteroid, building::Destructable, enemy::Enemy, station::Station},
+    },
+    screen::Paused,
+};
+
+use common::*;
+
+#[test]
+fn starts_in_build_phase_with_level_spawned() {
+    let mut app = headless_app(1);
+
+    assert_eq!(current_phase(&app), GamePhase::Build);
+    assert_eq!(app.world().resource::<GameplayManager>().cycle, 0);
+    assert_eq!(
+        app.world().resource::<Resources>().delivered,
+        ResourceBundle {
+            metal: 10,
+            crystal: 4,
+            ice: 4
+        }
+    );
+    assert_eq!(count::<Station>(&mut app), 1);
+    assert_eq!(count::<Destructable>(&mut app), 1);
+    // Two starting asteroids and three in each of the six belts
+    assert_eq!(count::<Asteroid>(&mut app), 20);
+    assert_eq!(count::<Enemy>(&mut app), 0);
+}
+
+#[test]
+fn cycles_through_all_phases() {
+    let mut app = headless_app(2);
+
+    run_until_phase(&mut app, GamePhase::Gather);
+    assert_eq!(count::<Enemy>(&mut app), 0);
+
+    run_until_phase(&mut app, GamePhase::Combat);
+    assert!(count::<Enemy>(&mut app) >= 5);
+
+    run_until_phase(&mut app, GamePhase::Build);
+    assert_eq!(app.world().resource::<GameplayManager>().cycle, 1);
+    assert_eq!(count::<Station>(&mut app), 1);
+}
+
+#[test]
+fn hard_difficulty_applies_to_the_run() {
+    let mut app = loaded_app(13);
+    app.insert_resource(Difficulty::preset(DifficultyPreset::Hard));
+    start_playing(&mut app);
+
+    assert_eq!(
+        app.world().resource::<Resources>().delivered,
+        ResourceBundle {
+            metal: 5,
+            crystal: 2,
+            ice: 2
+        }
+    );
+    let station_health = app
+        .world_mut()
+        .query_filtered::<&Destructable, With<Station>>()
+        .single(app.world())
+        .max_health();
+    assert_eq!(station_health, 1500.0);
+
+    // The first wave has five enemies on Normal
+    run_until_phase(&mut app, GamePhase::Combat);
+    assert!(count::<Enemy>(&mut app) >= 6);
+
+    run_until_phase(&mut app, GamePhase::Build);
+    let manager = app.world().resource::<GameplayManager>();
+    assert_eq!(manager.difficulty.preset, DifficultyPreset::Hard);
+    assert!((manager.enemy_damage_multiplier - 1.15).abs() < 1e-4);
+}
+
+#[test]
+fn pausing_freezes_the_run() {
+    let mut app = headless_app(19);
+    for _ in 0..5 {
+        app.update();
+    }
+    let manager = |app: &App| app.world().resource::<GameplayManager>().clone();
+
+    app.world_mut()
+        .resource_mut::<NextState<Paused>>()
+        .set(Paused(true));
+    app.update();
+    let paused_at = manager(&app);
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(app.world().resource::<Time<Virtual>>().is_paused());
+    assert_eq!(manager(&app).elapsed_time, paused_at.elapsed_time);
+    assert_eq!(
+        manager(&app).current_phase_time,
+        paused_at.current_phase_time
+    );
+
+    app.world_mut()
+        .resource_mut::<NextState<Paused>>()
+        .set(Paused(false));
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(manager(&app).elapsed_time > paused_at.elapsed_time);
+}
+
+#[test]
+fn readying_up_skips_to_gather_with_a_bonus() {
+    let mut app = headless_app(20);
+    app.update();
+    let before = app.world().resource::<Resources>().delivered;
+    let elapsed = app.world().resource::<GameplayManager>().elapsed_time;
+
+    app.world_mut().trigger(SkipBuildPhase);
+    let manager = app.world().resource::<GameplayManager>();
+    assert_eq!(
+        manager.current_phase_time,
+        phase_duration(&GamePhase::Build, 0)
+    );
+    assert!(manager.elapsed_time > elapsed + 15.0);
+    assert!(app.world().resource::<Resources>().delivered.metal >= before.metal + 4);
+
+    app.update();
+    app.update();
+    assert_eq!(current_phase(&app), GamePhase::Gather);
+    assert!(app.world().resource::<GameplayManager>().current_phase_time < 1.0);
+
+    // Readying up outside the Build phase does nothing
+    let delivered = app.world().resource::<Resources>().delivered;
+    app.world_mut().trigger(SkipBuildPhase);
+    assert_eq!(app.world().resource::<Resources>().delivered, delivered);
+}
@@ -0,0 +1,167 @@
+//! Saving and resuming runs, and the leaderboard of finished runs.
+
+mod common;
+
+use bevy::prelude::*;
+use tri_phase_defense::{
+    game::{
+        difficulty::{Difficulty, DifficultyPreset},
+        gameplay::{ResourceBundle, Resources},
+        phase::GamePhase,
+        save::{Resume, SaveRun, SavedRun, SAVE_KEY},
+        score::{Leaderboard, LeaderboardEntry, LEADERBOARD_KEY, LEADERBOARD_SIZE},
+        spawn::{asteroid::Asteroid, building::Destructable, enemy::Enemy, station::Station},
+        turret::{TargetingPolicy, Turret},
+    },
+    screen::Screen,
+};
+
+use common::*;
+
+#[test]
+fn saved_run_can_be_continued() {
+    let storage = temp_storage("continue");
+
+    let mut app = loaded_app(6);
+    app.insert_resource(storage.clone());
+    start_playing(&mut app);
+    spawn_damaged_building(&mut app, "turret", Vec3::new(30.0, 0.0, 0.0), 20.0);
+    app.world_mut()
+        .query::<&mut Turret>()
+        .single_mut(app.world_mut())
+        .targeting = TargetingPolicy::Sticky;
+    run_until_phase(&mut app, GamePhase::Gather);
+    let delivered = ResourceBundle {
+        metal: 42,
+        crystal: 3,
+        ice: 1,
+    };
+    app.world_mut().resource_mut::<Resources>().delivered = delivered;
+    app.world_mut().trigger(SaveRun);
+    app.update();
+    let asteroids = count::<Asteroid>(&mut app);
+
+    let saved_run = storage
+        .load::<SavedRun>(SAVE_KEY)
+        .expect("The run should have been saved");
+    assert_eq!(saved_run.seed, 6);
+    assert_eq!(saved_run.phase, GamePhase::Gather);
+    assert_eq!(saved_run.buildings.len(), 1);
+    assert_eq!(saved_run.buildings[0].health, 20.0);
+
+    // The difficulty picked for the next run is left alone
+    let mut app = loaded_app(7);
+    app.insert_resource(storage.clone());
+    app.insert_resource(Difficulty::preset(DifficultyPreset::Easy));
+    app.insert_resource(Resume(saved_run));
+    start_playing(&mut app);
+    app.update();
+
+    assert_eq!(current_phase(&app), GamePhase::Gather);
+    assert_eq!(app.world().resource::<Resources>().delivered, delivered);
+    assert_eq!(count::<Destructable>(&mut app), 2);
+    assert_eq!(count::<Asteroid>(&mut app), asteroids);
+    let health = app
+        .world_mut()
+        .query_filtered::<&Destructable, Without<Station>>()
+        .single(app.world())
+        .health;
+    assert_eq!(health, 20.0);
+    let targeting = app
+        .world_mut()
+        .query::<&Turret>()
+        .single(app.world())
+        .targeting;
+    assert_eq!(targeting, TargetingPolicy::Sticky);
+    let station_health = app
+        .world_mut()
+        .query_filtered::<&Destructable, With<Station>>()
+        .single(app.world())
+        .max_health();
+    assert_eq!(station_health, 2000.0);
+    assert_eq!(
+        app.world().resource::<Difficulty>().preset,
+        DifficultyPreset::Easy
+    );
+    assert!(!app.world().contains_resource::<Resume>());
+}
+
+#[test]
+fn quitting_to_title_keeps_the_save() {
+    let storage = temp_storage("quit");
+
+    let mut app = loaded_app(8);
+    app.insert_resource(storage.clone());
+    start_playing(&mut app);
+    app.world_mut().trigger(SaveRun);
+    app.world_mut()
+        .resource_mut::<NextState<Screen>>()
+        .set(Screen::Title);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    assert_eq!(
+        app.world().resource::<State<Screen>>().get(),
+        &Screen::Title
+    );
+    assert!(storage.contains(SAVE_KEY));
+}
+
+#[test]
+fn runs_saved_during_combat_keep_their_wave() {
+    let storage = temp_storage("combat");
+
+    let mut app = loaded_app(9);
+    app.insert_resource(storage.clone());
+    start_playing(&mut app);
+    run_until_phase(&mut app, GamePhase::Combat);
+    app.update();
+    let enemies = count::<Enemy>(&mut app);
+    assert!(enemies > 0);
+    app.world_mut().trigger(SaveRun);
+    app.update();
+
+    let saved_run = storage
+        .load::<SavedRun>(SAVE_KEY)
+        .expect("The run should have been saved");
+    assert_eq!(saved_run.phase, GamePhase::Combat);
+    assert_eq!(saved_run.enemies.len(), enemies);
+
+    // The wave isn't sent again on resuming
+    let mut app = loaded_app(9);
+    app.insert_resource(storage.clone());
+    app.insert_resource(Resume(saved_run));
+    start_playing(&mut app);
+    app.update();
+    app.update();
+
+    assert_eq!(current_phase(&app), GamePhase::Combat);
+    assert_eq!(count::<Enemy>(&mut app), enemies);
+}
+
+#[test]
+fn finished_runs_are_ranked_on_the_leaderboard() {
+    let storage = temp_storage("leaderboard");
+    let entry = |points| LeaderboardEntry {
+        points,
+        cycles: 1.0,
+        kills: 0,
+        seed: 0,
+        difficulty: DifficultyPreset::Normal,
+        // 2024-02-29
+        timestamp: 1_709_164_800,
+    };
+    assert_eq!(entry(0).date(), "2024-02-29");
+
+    assert_eq!(Leaderboard::record(&storage, entry(100)), Some(0));
+    assert_eq!(Leaderboard::record(&storage, entry(300)), Some(0));
+    assert_eq!(Leaderboard::record(&storage, entry(200)), Some(1));
+    for _ in 0..LEADERBOARD_SIZE {
+        Leaderboard::record(&storage, entry(1000));
+    }
+    // The table is full of better runs
+    assert_eq!(Leaderboard::record(&storage, entry(500)), None);
+    let leaderboard = storage.load::<Leaderboard>(LEADERBOARD_KEY).unwrap();
+    assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+}
@@ -0,0 +1,100 @@
+//! Input bindings and audio settings.
+
+mod common;
+
+use bevy::{
+    ecs::system::RunSystemOnce,
+    input::gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadInfo},
+    prelude::*,
+};
+use tri_phase_defense::{
+    game::{
+        audio::settings::{AudioSettings, AUDIO_SETTINGS_KEY},
+        input::{Action, Actions, InputBinding, InputMap, Stick},
+    },
+    HeadlessPlugin,
+};
+
+use common::*;
+
+#[test]
+fn rebound_actions_follow_the_new_key() {
+    let mut app = headless_app(16);
+    app.world_mut()
+        .resource_mut::<InputMap>()
+        .rebind(Action::ThrustRight, InputBinding::Key(KeyCode::KeyL));
+    let thrusting = |app: &mut App| {
+        app.world_mut()
+            .run_system_once(|actions: Actions| actions.pressed(Action::ThrustRight))
+    };
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::KeyD);
+    assert!(!thrusting(&mut app));
+
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::KeyL);
+    assert!(thrusting(&mut app));
+}
+
+#[test]
+fn gamepads_drive_actions_alongside_the_keyboard() {
+    let mut app = headless_app(17);
+    let gamepad = Gamepad::new(0);
+    app.world_mut().send_event(GamepadConnectionEvent {
+        gamepad,
+        connection: GamepadConnection::Connected(GamepadInfo {
+            name: "Test pad".to_string(),
+        }),
+    });
+    app.update();
+
+    // Rebinding the key keeps the default gamepad binding.
+    app.world_mut()
+        .resource_mut::<InputMap>()
+        .rebind(Action::ThrustRight, InputBinding::Key(KeyCode::KeyL));
+    app.world_mut()
+        .resource_mut::<ButtonInput<GamepadButton>>()
+        .press(GamepadButton::new(gamepad, GamepadButtonType::DPadRight));
+    app.world_mut()
+        .resource_mut::<Axis<GamepadAxis>>()
+        .set(GamepadAxis::new(gamepad, GamepadAxisType::RightStickX), 1.0);
+
+    let (thrusting, aim) = app.world_mut().run_system_once(|actions: Actions| {
+        (
+            actions.pressed(Action::ThrustRight),
+            actions.stick(Stick::Right),
+        )
+    });
+    assert!(thrusting);
+    assert_eq!(aim, Vec2::X);
+}
+
+#[test]
+fn audio_settings_are_loaded_at_startup() {
+    let storage = temp_storage("audio");
+    let saved = AudioSettings {
+        master: 0.5,
+        music: 0.4,
+        sfx: 1.0,
+        muted: false,
+    };
+    storage.save(AUDIO_SETTINGS_KEY, &saved);
+
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin);
+    app.insert_resource(storage);
+    app.update();
+
+    let settings = app.world().resource::<AudioSettings>();
+    assert_eq!(*settings, saved);
+    assert!((settings.music_volume() - 0.2).abs() < 1e-6);
+    assert_eq!(settings.sfx_volume(), 0.5);
+    let muted = AudioSettings {
+        muted: true,
+        ..settings.clone()
+    };
+    assert_eq!(muted.sfx_volume(), 0.0);
+}